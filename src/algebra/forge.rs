@@ -0,0 +1,18 @@
+#![allow(async_fn_in_trait)]
+
+use crate::domain::{Stats, StatsChange};
+
+use anyhow::Result;
+
+/// A code hosting provider the statistics are collected from
+pub trait Forge {
+    /// Name of the provider, used in logs
+    fn provider(&self) -> &'static str;
+    async fn collect_stats(&self) -> Result<Stats>;
+
+    /// Collects the parts of `stats` made stale by `changes` again.
+    /// Providers that can't collect a single part collect everything.
+    async fn refresh(&self, _stats: &Stats, _changes: &[StatsChange]) -> Result<Stats> {
+        self.collect_stats().await
+    }
+}
@@ -1,15 +1,21 @@
 #![allow(async_fn_in_trait)]
 
-use crate::domain::Stats;
-
-use anyhow::Result;
+use crate::domain::{
+    ContributionWeek, DailyViews, LinesChangedByRepo, PreflightReport, Stats, StatsError,
+};
 
 pub trait GithubExt {
-    type CalendarWeek;
-
-    async fn total_contributions(&self) -> Result<i64>;
-    async fn get_stats(&self) -> Result<Stats>;
-    async fn views(&self, repos: &[String]) -> Result<i64>;
-    async fn lines_changed(&self, repos: &[String]) -> Result<(i64, i64)>;
-    async fn contribution_calendar(&self) -> Result<Vec<Self::CalendarWeek>>;
+    /// Checks the token's identity, scopes and rate limit before any crawling
+    async fn preflight(&self) -> Result<PreflightReport, StatsError>;
+    async fn total_contributions(&self) -> Result<i64, StatsError>;
+    async fn get_stats(&self) -> Result<Stats, StatsError>;
+    /// Statistics aggregated over every repository of an organization
+    async fn get_org_stats(&self, org: &str) -> Result<Stats, StatsError>;
+    /// Daily views of each repository over the past 14 days
+    async fn views(&self, repos: &[String]) -> Result<Vec<DailyViews>, StatsError>;
+    /// Lines added and deleted by the configured user, per repository
+    async fn lines_changed(&self, repos: &[String]) -> Result<LinesChangedByRepo, StatsError>;
+    /// Pull request reviews over the past year
+    async fn reviews(&self) -> Result<i64, StatsError>;
+    async fn contribution_calendar(&self) -> Result<Vec<ContributionWeek>, StatsError>;
 }
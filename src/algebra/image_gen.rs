@@ -1,9 +1,26 @@
-use crate::domain::Stats;
-
-use anyhow::Result;
+use crate::domain::{CustomCard, Stats, StatsError, StatsMetric};
 
 pub trait ImageGenExt {
-    fn generate_overview(&self, stats: &Stats) -> Result<()>;
-    fn generate_languages(&self, stats: &Stats) -> Result<()>;
-    fn generate_contributions_grid(&self, stats: &Stats) -> Result<()>;
+    fn render_overview(&self, stats: &Stats) -> Result<String, StatsError>;
+    /// Renders the `limit` largest languages, or all of them when `None`
+    fn render_languages(&self, stats: &Stats, limit: Option<usize>) -> Result<String, StatsError>;
+    fn render_contributions_grid(&self, stats: &Stats) -> Result<String, StatsError>;
+    /// A self-contained HTML report with every card, the repositories and languages
+    /// as tables and the contribution calendar
+    fn render_dashboard(&self, stats: &Stats) -> Result<String, StatsError>;
+    /// A flat shields-style badge with the metric's label and value
+    fn render_badge(&self, stats: &Stats, metric: StatsMetric) -> Result<String, StatsError>;
+    /// Renders a card declared in the configuration from its own template
+    fn render_custom_card(&self, stats: &Stats, card: &CustomCard) -> Result<String, StatsError>;
+    fn generate_overview(&self, stats: &Stats) -> Result<(), StatsError>;
+    fn generate_languages(&self, stats: &Stats) -> Result<(), StatsError>;
+    fn generate_contributions_grid(&self, stats: &Stats) -> Result<(), StatsError>;
+    /// Writes the dashboard as `index.html`, ready to be published with GitHub Pages
+    fn generate_dashboard(&self, stats: &Stats) -> Result<(), StatsError>;
+    /// Writes one `badge_<metric>.svg` per metric
+    fn generate_badges(&self, stats: &Stats, metrics: &[StatsMetric]) -> Result<(), StatsError>;
+    /// Writes each custom card under its configured output name
+    fn generate_custom_cards(&self, stats: &Stats, cards: &[CustomCard]) -> Result<(), StatsError>;
+    /// Ranks team members, given as `(login, stats)`, and adds a team total row
+    fn generate_leaderboard(&self, team: &[(String, Stats)]) -> Result<(), StatsError>;
 }
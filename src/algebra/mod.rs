@@ -1,5 +1,7 @@
+mod forge;
 mod github;
 mod image_gen;
 
+pub use forge::*;
 pub use github::*;
 pub use image_gen::*;
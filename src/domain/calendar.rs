@@ -0,0 +1,170 @@
+use std::collections::BTreeMap;
+
+use chrono::{Datelike, NaiveDate, TimeDelta};
+
+/// GitHub's calendar colors, from no contributions to the busiest days
+pub const CALENDAR_COLORS: [&str; 5] = ["#ebedf0", "#9be9a8", "#40c463", "#30a14e", "#216e39"];
+
+/// A single square of the contribution calendar
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContributionDay {
+    date: NaiveDate,
+    count: i64,
+    /// From 0 (no contributions) to 4 (busiest quartile)
+    level: u8,
+}
+
+impl ContributionDay {
+    pub fn new(date: NaiveDate, count: i64, level: u8) -> Self {
+        Self {
+            date,
+            count,
+            level: level.min(4),
+        }
+    }
+
+    pub fn date(&self) -> NaiveDate {
+        self.date
+    }
+
+    pub fn count(&self) -> i64 {
+        self.count
+    }
+
+    pub fn level(&self) -> u8 {
+        self.level
+    }
+
+    pub fn color(&self) -> &'static str {
+        CALENDAR_COLORS[self.level as usize]
+    }
+}
+
+/// A column of the contribution calendar, starting on Sunday
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ContributionWeek {
+    days: Vec<ContributionDay>,
+}
+
+impl ContributionWeek {
+    pub fn new(days: Vec<ContributionDay>) -> Self {
+        Self { days }
+    }
+
+    pub fn days(&self) -> &[ContributionDay] {
+        &self.days
+    }
+}
+
+/// Builds a GitHub-like calendar of the 53 weeks up to `today` from daily
+/// contribution counts, for providers that only report counts.
+pub fn calendar_from_counts(
+    counts: &BTreeMap<NaiveDate, i64>,
+    today: NaiveDate,
+) -> Vec<ContributionWeek> {
+    // weeks start on Sunday, like on GitHub
+    let first_sunday =
+        today - TimeDelta::days(52 * 7 + today.weekday().num_days_from_sunday() as i64);
+    // days outside the calendar don't set its scale
+    let max = counts
+        .range(first_sunday..=today)
+        .map(|(_, count)| *count)
+        .max()
+        .unwrap_or_default();
+
+    first_sunday
+        .iter_days()
+        .take_while(|date| *date <= today)
+        .collect::<Vec<_>>()
+        .chunks(7)
+        .map(|week| {
+            ContributionWeek::new(
+                week.iter()
+                    .map(|date| {
+                        let count = counts.get(date).copied().unwrap_or_default();
+                        let level = if count == 0 || max == 0 {
+                            0
+                        } else {
+                            ((count * 4 + max - 1) / max).clamp(1, 4) as u8
+                        };
+                        ContributionDay::new(*date, count, level)
+                    })
+                    .collect(),
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Weekday;
+
+    use super::*;
+
+    fn date(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, month, day).unwrap()
+    }
+
+    #[test]
+    fn weeks_start_on_sunday_and_end_today() {
+        // a Monday, the last week only has two days so far
+        let today = date(10, 19);
+
+        let calendar = calendar_from_counts(&BTreeMap::new(), today);
+
+        assert_eq!(calendar.len(), 53);
+        assert!(calendar
+            .iter()
+            .all(|week| week.days()[0].date().weekday() == Weekday::Sun));
+        assert!(calendar[..52].iter().all(|week| week.days().len() == 7));
+        assert_eq!(calendar[0].days()[0].date(), today - TimeDelta::days(365));
+        let last = calendar.last().unwrap().days();
+        assert_eq!(last.len(), 2);
+        assert_eq!(last[1].date(), today);
+        assert!(calendar
+            .iter()
+            .flat_map(ContributionWeek::days)
+            .all(|day| day.level() == 0));
+    }
+
+    #[test]
+    fn levels_are_quartiles_of_the_busiest_day() {
+        let counts = [
+            (1, 0),
+            (2, 1),
+            (3, 2),
+            (4, 3),
+            (5, 4),
+            (6, 6),
+            (7, 7),
+            (8, 8),
+        ]
+        .into_iter()
+        .map(|(day, count)| (date(10, day), count))
+        // outside the calendar, it doesn't raise the busiest day
+        .chain([(date(10, 20), 100)])
+        .collect::<BTreeMap<_, _>>();
+
+        let calendar = calendar_from_counts(&counts, date(10, 19));
+
+        let levels = calendar
+            .iter()
+            .flat_map(ContributionWeek::days)
+            .filter(|day| day.date() <= date(10, 8) && day.date() >= date(10, 1))
+            .map(|day| (day.count(), day.level()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            levels,
+            [
+                (0, 0),
+                (1, 1),
+                (2, 1),
+                (3, 2),
+                (4, 2),
+                (6, 3),
+                (7, 4),
+                (8, 4)
+            ]
+        );
+    }
+}
@@ -1,18 +1,29 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ContributorActivity {
-    author: Author,
+    /// `null` for commits of deleted accounts, which GitHub attributes to its ghost user
+    author: Option<Author>,
+    /// Total amount of commits authored by the contributor
+    total: i64,
     weeks: Vec<Week>,
 }
 
 impl ContributorActivity {
+    pub fn author(&self) -> Option<&Author> {
+        self.author.as_ref()
+    }
+
+    pub fn total(&self) -> i64 {
+        self.total
+    }
+
     pub fn weeks(&self) -> &Vec<Week> {
         self.weeks.as_ref()
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Week {
     #[serde(rename = "a")]
     added: i64,
@@ -30,7 +41,7 @@ impl Week {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Author {
     login: String,
 }
@@ -0,0 +1,325 @@
+use std::cmp::Reverse;
+
+use serde::Deserialize;
+
+use super::{compact, Stats, StatsMetric};
+
+/// Files the built-in cards and exports are written as, which a card mustn't replace
+const RESERVED_OUTPUTS: [&str; 14] = [
+    "overview.svg",
+    "overview_dark.svg",
+    "languages.svg",
+    "languages_dark.svg",
+    "contribution_grid.svg",
+    "contribution_grid_dark.svg",
+    "leaderboard.svg",
+    "index.html",
+    "stats.json",
+    "metrics",
+    "contribution_calendar.csv",
+    "languages.csv",
+    "repositories.csv",
+    "traffic.csv",
+];
+
+/// Repositories listed by a card without a `limit`
+const DEFAULT_REPOSITORY_ROWS: usize = 10;
+
+/// A card declared in the configuration, rendered from its own template.
+///
+/// Besides every metric as `{{ <metric> }}` and the account as `{{ name }}`, the template
+/// gets `{{ rows }}`: `row` repeated for each metric, language or repository, in order.
+#[derive(Debug, Clone, Deserialize)]
+pub struct CustomCard {
+    /// Path of the template
+    template: String,
+    /// File name the card is written as in the output folder
+    output: String,
+    /// What each row shows
+    #[serde(default)]
+    rows: CardRows,
+    /// The metrics listed when `rows` is `metrics`
+    #[serde(default)]
+    metrics: Vec<StatsMetric>,
+    /// How many languages or repositories are listed; `LANGUAGES_LIMIT` languages,
+    /// or the ten most starred repositories when unset
+    limit: Option<usize>,
+    /// Markup of a single row, with `{{ label }}`, `{{ value }}`, `{{ color }}`, `{{ index }}`,
+    /// `{{ y }}`, the row's baseline, and `{{ delay }}`, a staggered animation delay in
+    /// milliseconds, replaced
+    #[serde(default = "default_row")]
+    row: String,
+    /// Height of a row in pixels, the card grows by it for each row
+    #[serde(default = "default_row_height")]
+    row_height: u32,
+}
+
+/// The source of a custom card's rows
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CardRows {
+    /// One row per listed metric
+    #[default]
+    Metrics,
+    /// One row per language, largest first, with its share as the value
+    Languages,
+    /// One row per repository, most starred first, with its stars as the value
+    Repositories,
+}
+
+/// The values filled into one row of a custom card
+#[derive(Debug, Clone, PartialEq)]
+pub struct CardRow {
+    label: String,
+    /// `None` when the metric is unavailable
+    value: Option<String>,
+    color: String,
+}
+
+impl CardRow {
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    pub fn value(&self) -> Option<&str> {
+        self.value.as_deref()
+    }
+
+    pub fn color(&self) -> &str {
+        &self.color
+    }
+}
+
+impl CustomCard {
+    pub fn template(&self) -> &str {
+        &self.template
+    }
+
+    pub fn output(&self) -> &str {
+        &self.output
+    }
+
+    pub fn metrics(&self) -> &[StatsMetric] {
+        &self.metrics
+    }
+
+    pub fn row(&self) -> &str {
+        &self.row
+    }
+
+    pub fn row_height(&self) -> u32 {
+        self.row_height
+    }
+
+    /// The rows the card lists for `stats`
+    pub fn rows(&self, stats: &Stats) -> Vec<CardRow> {
+        match self.rows {
+            CardRows::Metrics => self
+                .metrics
+                .iter()
+                .map(|metric| CardRow {
+                    label: capitalize(metric.label()),
+                    value: metric.value(stats),
+                    color: metric.color(stats),
+                })
+                .collect(),
+            CardRows::Languages => stats
+                .all_languages()
+                .iter()
+                .take(self.limit.unwrap_or(stats.languages().len()))
+                .map(|(name, language)| CardRow {
+                    label: name.clone(),
+                    value: Some(format!("{:.2}%", language.proportion())),
+                    color: language.color().to_string(),
+                })
+                .collect(),
+            CardRows::Repositories => {
+                let mut repos = stats.repos().iter().collect::<Vec<_>>();
+                repos.sort_by_key(|repo| Reverse(repo.stargazers()));
+                repos
+                    .into_iter()
+                    .take(self.limit.unwrap_or(DEFAULT_REPOSITORY_ROWS))
+                    .map(|repo| CardRow {
+                        label: repo.name_with_owner().to_string(),
+                        value: Some(compact(repo.stargazers())),
+                        color: repo
+                            .languages()
+                            .iter()
+                            .max_by_key(|language| language.size())
+                            .map(|language| language.color().to_string())
+                            .unwrap_or_default(),
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+/// Checks that every card writes a file of its own right into the output folder,
+/// so none escapes it or replaces a built-in card, export or another custom card
+pub fn validate_cards(cards: &[CustomCard]) -> Result<(), String> {
+    for (idx, card) in cards.iter().enumerate() {
+        let output = card.output();
+        if output.is_empty() || output == "." || output == ".." {
+            return Err(format!("card output `{output}` is not a file name"));
+        }
+        if output.contains(['/', '\\']) {
+            return Err(format!(
+                "card output `{output}` must be a file name, without directories"
+            ));
+        }
+        let is_badge = output.starts_with("badge_") && output.ends_with(".svg");
+        if is_badge || RESERVED_OUTPUTS.contains(&output) {
+            return Err(format!(
+                "card output `{output}` is the name of a built-in file"
+            ));
+        }
+        if cards[..idx].iter().any(|other| other.output() == output) {
+            return Err(format!("card output `{output}` is used by several cards"));
+        }
+    }
+    Ok(())
+}
+
+/// `lines changed` as `Lines changed`
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+/// A label and its value in the style of the overview card, the value aligned right
+fn default_row() -> String {
+    r#"<g class="row" style="animation-delay: {{ delay }}ms">
+<text class="label" x="26" y="{{ y }}">{{ label }}</text>
+<text class="value" x="334" y="{{ y }}" text-anchor="end">{{ value }}</text>
+</g>"#
+        .to_string()
+}
+
+fn default_row_height() -> u32 {
+    24
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::domain::{Language, Metric, RepoSource, Repository, StatsBuilder};
+
+    fn card(fields: serde_json::Value) -> CustomCard {
+        let mut card = json!({ "template": "card.svg", "output": "card.svg" });
+        card.as_object_mut()
+            .unwrap()
+            .extend(fields.as_object().unwrap().clone());
+        serde_json::from_value(card).unwrap()
+    }
+
+    fn stats() -> Stats {
+        let repo = |name: &str, stars| {
+            Repository::new(
+                name.to_string(),
+                name.to_string(),
+                stars,
+                0,
+                false,
+                false,
+                false,
+                false,
+                None,
+                vec![],
+                RepoSource::Owned,
+            )
+        };
+        let language = |name: &str, size| {
+            let mut language = Language::new(name.to_string(), size, 1, "#abc".to_string(), 0.0);
+            language.set_proportion(1000);
+            (name.to_string(), language)
+        };
+        StatsBuilder::default()
+            .name("jane".to_string())
+            .stargazers(1600)
+            .forks(0)
+            .languages(vec![
+                language("Rust", 600),
+                language("Go", 300),
+                language("Shell", 100),
+            ])
+            .languages_limit(2)
+            .repos(vec![
+                repo("jane/small", 100),
+                repo("jane/big", 1500),
+                repo("jane/none", 0),
+            ])
+            .total_contributions(Metric::Available(12))
+            .lines_changed_by_repo(Metric::Unavailable("no clones".to_string()))
+            .views(Metric::Available(vec![]))
+            .contribution_calendar(Metric::Available(vec![]))
+            .build()
+            .unwrap()
+    }
+
+    fn labels(rows: &[CardRow]) -> Vec<&str> {
+        rows.iter().map(CardRow::label).collect()
+    }
+
+    #[test]
+    fn metric_rows_follow_the_listed_metrics() {
+        let card = card(json!({ "metrics": ["stars", "lines_changed"] }));
+
+        let rows = card.rows(&stats());
+
+        assert_eq!(labels(&rows), ["Stars", "Lines changed"]);
+        assert_eq!(rows[0].value(), Some("1.6k"));
+        assert_eq!(rows[1].value(), None);
+    }
+
+    #[test]
+    fn language_rows_default_to_the_cards_limit() {
+        let rows = card(json!({ "rows": "languages" })).rows(&stats());
+        assert_eq!(labels(&rows), ["Rust", "Go"]);
+        assert_eq!(rows[0].value(), Some("60.00%"));
+        assert_eq!(rows[0].color(), "#abc");
+
+        let rows = card(json!({ "rows": "languages", "limit": 5 })).rows(&stats());
+        assert_eq!(labels(&rows), ["Rust", "Go", "Shell"]);
+    }
+
+    #[test]
+    fn repository_rows_are_the_most_starred() {
+        let rows = card(json!({ "rows": "repositories", "limit": 2 })).rows(&stats());
+
+        assert_eq!(labels(&rows), ["jane/big", "jane/small"]);
+        assert_eq!(rows[0].value(), Some("1.5k"));
+    }
+
+    #[test]
+    fn outputs_stay_in_the_output_folder_and_off_built_in_names() {
+        let outputs = |outputs: &[&str]| {
+            let cards = outputs
+                .iter()
+                .map(|output| card(json!({ "output": output })))
+                .collect::<Vec<_>>();
+            validate_cards(&cards)
+        };
+
+        assert!(outputs(&["highlights.svg", "repos.svg"]).is_ok());
+        for invalid in [
+            "../escape.svg",
+            "nested/card.svg",
+            "..\\escape.svg",
+            "..",
+            "",
+            "overview.svg",
+            "overview_dark.svg",
+            "stats.json",
+            "badge_stars.svg",
+        ] {
+            assert!(outputs(&[invalid]).is_err(), "{invalid}");
+        }
+        assert!(outputs(&["twice.svg", "twice.svg"]).is_err());
+    }
+}
@@ -0,0 +1,76 @@
+use chrono::{DateTime, Utc};
+
+use super::StatsBuilderError;
+
+/// The underlying error of a typed failure
+pub type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// The failures a caller may want to react to differently: renewing the
+/// credentials, waiting for the rate limit to reset, or fixing the configuration.
+/// Anything else is kept as `Other` with its context chain intact.
+#[derive(Debug, thiserror::Error)]
+pub enum StatsError {
+    #[error("authentication failed: {message}")]
+    Auth {
+        message: String,
+        #[source]
+        source: Option<BoxError>,
+    },
+    #[error(
+        "rate limited until {}",
+        .reset_at.map_or("an unknown time".to_string(), |reset_at| reset_at.to_rfc3339())
+    )]
+    RateLimited { reset_at: Option<DateTime<Utc>> },
+    #[error("not found: {0}")]
+    NotFound(String),
+    /// The response didn't have the expected shape
+    #[error("unexpected response: {message}")]
+    Schema {
+        message: String,
+        #[source]
+        source: Option<BoxError>,
+    },
+    #[error("template {path} is missing")]
+    TemplateMissing {
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+    #[error(transparent)]
+    Other(anyhow::Error),
+}
+
+impl StatsError {
+    pub fn auth(message: impl Into<String>) -> Self {
+        StatsError::Auth {
+            message: message.into(),
+            source: None,
+        }
+    }
+
+    pub fn schema(message: impl Into<String>) -> Self {
+        StatsError::Schema {
+            message: message.into(),
+            source: None,
+        }
+    }
+}
+
+impl From<anyhow::Error> for StatsError {
+    /// Recovers a typed error that was passed through `anyhow`
+    fn from(error: anyhow::Error) -> Self {
+        error
+            .downcast::<StatsError>()
+            .unwrap_or_else(StatsError::Other)
+    }
+}
+
+impl From<StatsBuilderError> for StatsError {
+    fn from(error: StatsBuilderError) -> Self {
+        StatsError::Other(error.into())
+    }
+}
@@ -0,0 +1,112 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GiteaUser {
+    id: i64,
+    login: String,
+    #[serde(default)]
+    full_name: String,
+    avatar_url: Option<String>,
+}
+
+impl GiteaUser {
+    pub fn id(&self) -> i64 {
+        self.id
+    }
+
+    pub fn login(&self) -> &str {
+        &self.login
+    }
+
+    /// The full name, falling back to the login when it isn't set
+    pub fn name(&self) -> &str {
+        if self.full_name.is_empty() {
+            &self.login
+        } else {
+            &self.full_name
+        }
+    }
+
+    pub fn avatar_url(&self) -> Option<&str> {
+        self.avatar_url.as_deref()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GiteaRepo {
+    id: i64,
+    full_name: String,
+    owner: GiteaOwner,
+    #[serde(default)]
+    stars_count: i64,
+    #[serde(default)]
+    forks_count: i64,
+    #[serde(default)]
+    fork: bool,
+    #[serde(default)]
+    archived: bool,
+    #[serde(default)]
+    private: bool,
+    updated_at: Option<DateTime<Utc>>,
+}
+
+impl GiteaRepo {
+    pub fn id(&self) -> i64 {
+        self.id
+    }
+
+    pub fn full_name(&self) -> &str {
+        &self.full_name
+    }
+
+    pub fn owner(&self) -> &str {
+        &self.owner.login
+    }
+
+    pub fn stars_count(&self) -> i64 {
+        self.stars_count
+    }
+
+    pub fn forks_count(&self) -> i64 {
+        self.forks_count
+    }
+
+    pub fn fork(&self) -> bool {
+        self.fork
+    }
+
+    pub fn archived(&self) -> bool {
+        self.archived
+    }
+
+    pub fn private(&self) -> bool {
+        self.private
+    }
+
+    pub fn updated_at(&self) -> Option<DateTime<Utc>> {
+        self.updated_at
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GiteaOwner {
+    login: String,
+}
+
+/// One bucket of the user heatmap, timestamps are unix seconds
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GiteaHeatmapEntry {
+    timestamp: i64,
+    contributions: i64,
+}
+
+impl GiteaHeatmapEntry {
+    pub fn date(&self) -> Option<NaiveDate> {
+        DateTime::from_timestamp(self.timestamp, 0).map(|dt| dt.date_naive())
+    }
+
+    pub fn contributions(&self) -> i64 {
+        self.contributions
+    }
+}
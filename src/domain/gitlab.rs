@@ -0,0 +1,141 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GitlabUser {
+    id: i64,
+    username: String,
+    name: String,
+    avatar_url: Option<String>,
+    /// Only returned for the authenticated user
+    email: Option<String>,
+    commit_email: Option<String>,
+}
+
+impl GitlabUser {
+    pub fn id(&self) -> i64 {
+        self.id
+    }
+
+    pub fn username(&self) -> &str {
+        &self.username
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn avatar_url(&self) -> Option<&str> {
+        self.avatar_url.as_deref()
+    }
+
+    /// Emails the user's commits may be authored with
+    pub fn emails(&self) -> Vec<&str> {
+        self.email
+            .iter()
+            .chain(self.commit_email.iter())
+            .map(String::as_str)
+            .collect()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GitlabProject {
+    id: i64,
+    path_with_namespace: String,
+    star_count: i64,
+    forks_count: Option<i64>,
+    #[serde(default)]
+    archived: bool,
+    visibility: Option<String>,
+    namespace: GitlabNamespace,
+    forked_from_project: Option<serde_json::Value>,
+    last_activity_at: Option<DateTime<Utc>>,
+}
+
+impl GitlabProject {
+    pub fn id(&self) -> i64 {
+        self.id
+    }
+
+    pub fn path_with_namespace(&self) -> &str {
+        &self.path_with_namespace
+    }
+
+    pub fn star_count(&self) -> i64 {
+        self.star_count
+    }
+
+    pub fn forks_count(&self) -> i64 {
+        self.forks_count.unwrap_or_default()
+    }
+
+    pub fn archived(&self) -> bool {
+        self.archived
+    }
+
+    pub fn is_private(&self) -> bool {
+        self.visibility.as_deref().is_some_and(|v| v != "public")
+    }
+
+    pub fn is_fork(&self) -> bool {
+        self.forked_from_project.is_some()
+    }
+
+    /// Projects in a group namespace are the GitLab equivalent of organization repositories
+    pub fn is_in_group(&self) -> bool {
+        self.namespace.kind == "group"
+    }
+
+    pub fn last_activity_at(&self) -> Option<DateTime<Utc>> {
+        self.last_activity_at
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GitlabNamespace {
+    kind: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GitlabEvent {
+    created_at: DateTime<Utc>,
+}
+
+impl GitlabEvent {
+    pub fn date(&self) -> NaiveDate {
+        self.created_at.date_naive()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GitlabCommit {
+    author_email: String,
+    #[serde(default)]
+    parent_ids: Vec<String>,
+    stats: Option<GitlabCommitStats>,
+}
+
+impl GitlabCommit {
+    pub fn author_email(&self) -> &str {
+        &self.author_email
+    }
+
+    pub fn is_merge(&self) -> bool {
+        self.parent_ids.len() > 1
+    }
+
+    pub fn additions(&self) -> i64 {
+        self.stats.as_ref().map(|s| s.additions).unwrap_or_default()
+    }
+
+    pub fn deletions(&self) -> i64 {
+        self.stats.as_ref().map(|s| s.deletions).unwrap_or_default()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GitlabCommitStats {
+    additions: i64,
+    deletions: i64,
+}
@@ -2,35 +2,76 @@ use graphql_client::GraphQLQuery;
 
 type DateTime = String;
 type Date = String;
+#[allow(clippy::upper_case_acronyms)]
+type URI = String;
 
-#[derive(GraphQLQuery)]
-#[graphql(
-    schema_path = "resources/graphql/schema.graphql",
-    query_path = "resources/graphql/contribution_years.graphql",
-    response_derives = "Debug"
-)]
-pub struct ContributionYears;
-
-#[derive(GraphQLQuery)]
-#[graphql(
-    schema_path = "resources/graphql/schema.graphql",
-    query_path = "resources/graphql/repos_overview.graphql",
-    response_derives = "Debug"
-)]
-pub struct ReposOverview;
-
-#[derive(Debug, GraphQLQuery)]
-#[graphql(
-    schema_path = "resources/graphql/schema.graphql",
-    query_path = "resources/graphql/contributions_by_year.graphql",
-    response_derives = "Debug"
-)]
-pub struct ContributionsByYear;
-
-#[derive(GraphQLQuery)]
-#[graphql(
-    schema_path = "resources/graphql/schema.graphql",
-    query_path = "resources/graphql/contribution_calendar.graphql",
-    response_derives = "Debug,Clone"
-)]
-pub struct ContributionCalendar;
+/// Declares a query checked against the github.com schema, or against the
+/// GitHub Enterprise Server schema when built with the `ghes` feature.
+macro_rules! github_query {
+    ($(#[$meta:meta])* $name:ident, $query_path:literal, $response_derives:literal) => {
+        $(#[$meta])*
+        #[derive(GraphQLQuery)]
+        #[cfg_attr(
+            not(feature = "ghes"),
+            graphql(
+                schema_path = "resources/graphql/schema.graphql",
+                query_path = $query_path,
+                response_derives = $response_derives
+            )
+        )]
+        #[cfg_attr(
+            feature = "ghes",
+            graphql(
+                schema_path = "resources/graphql/ghes/schema.graphql",
+                query_path = $query_path,
+                response_derives = $response_derives
+            )
+        )]
+        pub struct $name;
+    };
+}
+
+github_query!(
+    ContributionYears,
+    "resources/graphql/contribution_years.graphql",
+    "Debug"
+);
+
+github_query!(
+    ReposOverview,
+    "resources/graphql/repos_overview.graphql",
+    "Debug"
+);
+
+github_query!(
+    OrgOverview,
+    "resources/graphql/org_overview.graphql",
+    "Debug"
+);
+
+github_query!(
+    #[derive(Debug)]
+    ContributionsByYear,
+    "resources/graphql/contributions_by_year.graphql",
+    "Debug"
+);
+
+github_query!(
+    ContributionCalendar,
+    "resources/graphql/contribution_calendar.graphql",
+    "Debug,Clone"
+);
+
+github_query!(
+    PullRequestReviews,
+    "resources/graphql/pull_request_reviews.graphql",
+    "Debug"
+);
+
+github_query!(Preflight, "resources/graphql/preflight.graphql", "Debug");
+
+github_query!(
+    AppPreflight,
+    "resources/graphql/app_preflight.graphql",
+    "Debug"
+);
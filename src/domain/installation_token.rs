@@ -0,0 +1,58 @@
+use chrono::{DateTime, TimeDelta, Utc};
+use secrecy::SecretString;
+use serde::{Deserialize, Serialize};
+
+/// Short-lived token a GitHub App receives for one of its installations
+#[derive(Deserialize, Debug, Clone)]
+pub struct InstallationToken {
+    token: SecretString,
+    expires_at: DateTime<Utc>,
+}
+
+impl InstallationToken {
+    pub fn token(&self) -> &SecretString {
+        &self.token
+    }
+
+    pub fn expires_at(&self) -> DateTime<Utc> {
+        self.expires_at
+    }
+
+    /// Whether the token expires within `margin` of `now`
+    pub fn expires_within(&self, margin: TimeDelta, now: DateTime<Utc>) -> bool {
+        self.expires_at - margin <= now
+    }
+}
+
+/// Claims of the JWT a GitHub App authenticates itself with
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct AppClaims {
+    /// Issued at, backdated to allow for clock drift
+    iat: i64,
+    /// Expiration, GitHub accepts at most ten minutes after `iat`
+    exp: i64,
+    /// The App ID
+    iss: String,
+}
+
+impl AppClaims {
+    pub fn new(app_id: &str, now: DateTime<Utc>) -> Self {
+        Self {
+            iat: (now - TimeDelta::seconds(60)).timestamp(),
+            exp: (now + TimeDelta::minutes(9)).timestamp(),
+            iss: app_id.to_string(),
+        }
+    }
+
+    pub fn iat(&self) -> i64 {
+        self.iat
+    }
+
+    pub fn exp(&self) -> i64 {
+        self.exp
+    }
+
+    pub fn iss(&self) -> &str {
+        &self.iss
+    }
+}
@@ -0,0 +1,75 @@
+use std::fmt::Display;
+
+/// A metric that is either collected or unavailable, with the reason it couldn't be collected
+#[derive(Debug, Clone)]
+pub enum Metric<T> {
+    Available(T),
+    Unavailable(String),
+}
+
+impl<T> Metric<T> {
+    /// Keeps a failed metric as unavailable when partial results are allowed,
+    /// otherwise hands the error back so the run fails
+    pub fn from_result<E: Display>(
+        name: &str,
+        result: Result<T, E>,
+        allow_partial: bool,
+    ) -> Result<Self, E> {
+        match result {
+            Ok(value) => Ok(Metric::Available(value)),
+            Err(e) if allow_partial => {
+                let reason = format!("{e:#}");
+                tracing::warn!("{} will be unavailable: {}", name, reason);
+                Ok(Metric::Unavailable(reason))
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn value(&self) -> Option<&T> {
+        match self {
+            Metric::Available(value) => Some(value),
+            Metric::Unavailable(_) => None,
+        }
+    }
+
+    pub fn reason(&self) -> Option<&str> {
+        match self {
+            Metric::Available(_) => None,
+            Metric::Unavailable(reason) => Some(reason),
+        }
+    }
+
+    pub fn is_available(&self) -> bool {
+        matches!(self, Metric::Available(_))
+    }
+
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Metric<U> {
+        match self {
+            Metric::Available(value) => Metric::Available(f(value)),
+            Metric::Unavailable(reason) => Metric::Unavailable(reason),
+        }
+    }
+
+    /// Combines two metrics, unavailable if either of them is
+    pub fn zip_with<U, R>(self, other: Metric<U>, f: impl FnOnce(T, U) -> R) -> Metric<R> {
+        match (self, other) {
+            (Metric::Available(a), Metric::Available(b)) => Metric::Available(f(a, b)),
+            (Metric::Unavailable(reason), _) | (_, Metric::Unavailable(reason)) => {
+                Metric::Unavailable(reason)
+            }
+        }
+    }
+}
+
+impl<T> From<T> for Metric<T> {
+    fn from(value: T) -> Self {
+        Metric::Available(value)
+    }
+}
+
+impl<T: Default> Default for Metric<T> {
+    fn default() -> Self {
+        Metric::Available(T::default())
+    }
+}
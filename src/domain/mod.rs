@@ -1,9 +1,35 @@
+mod calendar;
 mod contributor_activity;
+mod custom_card;
+mod error;
+mod gitea;
+mod gitlab;
 mod graphql;
+mod installation_token;
+mod metric;
+mod preflight_report;
+mod repo_history;
+mod repository;
 mod stats;
+mod stats_change;
+mod stats_metric;
+mod stats_summary;
 mod view;
 
+pub use calendar::*;
 pub use contributor_activity::*;
+pub use custom_card::*;
+pub use error::*;
+pub use gitea::*;
+pub use gitlab::*;
 pub use graphql::*;
+pub use installation_token::*;
+pub use metric::*;
+pub use preflight_report::*;
+pub use repo_history::*;
+pub use repository::*;
 pub use stats::*;
+pub use stats_change::*;
+pub use stats_metric::*;
+pub use stats_summary::*;
 pub use view::*;
@@ -0,0 +1,189 @@
+use std::fmt::{self, Display};
+
+use chrono::{DateTime, Utc};
+
+/// What the token can see, checked before any crawling starts
+#[derive(Debug, Clone)]
+pub struct PreflightReport {
+    /// `None` for GitHub App installations, which act as no user
+    viewer_login: Option<String>,
+    actor: String,
+    /// `None` for fine-grained and GitHub App tokens, which don't report scopes
+    scopes: Option<Vec<String>>,
+    rate_limit: RateLimit,
+    metrics: Vec<MetricAvailability>,
+}
+
+impl PreflightReport {
+    pub fn new(
+        viewer_login: Option<String>,
+        actor: String,
+        scopes: Option<Vec<String>>,
+        rate_limit: RateLimit,
+        metrics: Vec<MetricAvailability>,
+    ) -> Self {
+        Self {
+            viewer_login,
+            actor,
+            scopes,
+            rate_limit,
+            metrics,
+        }
+    }
+
+    pub fn viewer_login(&self) -> Option<&str> {
+        self.viewer_login.as_deref()
+    }
+
+    pub fn actor(&self) -> &str {
+        &self.actor
+    }
+
+    /// Always true for App installations, which have no user to compare against
+    pub fn actor_matches_viewer(&self) -> bool {
+        self.viewer_login
+            .as_ref()
+            .is_none_or(|login| login.eq_ignore_ascii_case(&self.actor))
+    }
+
+    pub fn scopes(&self) -> Option<&[String]> {
+        self.scopes.as_deref()
+    }
+
+    pub fn rate_limit(&self) -> &RateLimit {
+        &self.rate_limit
+    }
+
+    pub fn metrics(&self) -> &[MetricAvailability] {
+        &self.metrics
+    }
+
+    pub fn unavailable(&self) -> impl Iterator<Item = &MetricAvailability> {
+        self.metrics.iter().filter(|metric| !metric.is_available())
+    }
+}
+
+impl Display for PreflightReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.viewer_login {
+            Some(login) => writeln!(f, "Token owner:  {login}")?,
+            None => writeln!(f, "Token owner:  GitHub App installation")?,
+        }
+        writeln!(f, "GitHub actor: {}", self.actor)?;
+        match &self.scopes {
+            Some(scopes) if scopes.is_empty() => writeln!(f, "Scopes:       (none)")?,
+            Some(scopes) => writeln!(f, "Scopes:       {}", scopes.join(", "))?,
+            None => writeln!(f, "Scopes:       n/a (fine-grained or GitHub App token)")?,
+        }
+        writeln!(
+            f,
+            "Rate limit:   {}/{} remaining, resets at {}",
+            self.rate_limit.remaining(),
+            self.rate_limit.limit(),
+            self.rate_limit.reset_at()
+        )?;
+        writeln!(f)?;
+
+        let width = self
+            .metrics
+            .iter()
+            .map(|metric| metric.metric().len())
+            .max()
+            .unwrap_or_default()
+            .max("Metric".len());
+        writeln!(f, "{:<width$}  {:<11}  Reason", "Metric", "Status")?;
+        writeln!(f, "{:-<width$}  {:-<11}  {:-<6}", "", "", "")?;
+        for metric in &self.metrics {
+            let status = if metric.is_available() {
+                "available"
+            } else {
+                "unavailable"
+            };
+            writeln!(
+                f,
+                "{:<width$}  {:<11}  {}",
+                metric.metric(),
+                status,
+                metric.reason().unwrap_or_default()
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RateLimit {
+    limit: i64,
+    remaining: i64,
+    reset_at: DateTime<Utc>,
+}
+
+impl RateLimit {
+    pub fn new(limit: i64, remaining: i64, reset_at: DateTime<Utc>) -> Self {
+        Self {
+            limit,
+            remaining,
+            reset_at,
+        }
+    }
+
+    pub fn limit(&self) -> i64 {
+        self.limit
+    }
+
+    pub fn remaining(&self) -> i64 {
+        self.remaining
+    }
+
+    pub fn reset_at(&self) -> DateTime<Utc> {
+        self.reset_at
+    }
+}
+
+/// Whether a metric can be collected with the token, and why not
+#[derive(Debug, Clone)]
+pub struct MetricAvailability {
+    metric: String,
+    available: bool,
+    /// Why the metric is unavailable, or a caveat when it's only partially available
+    reason: Option<String>,
+}
+
+impl MetricAvailability {
+    pub fn available(metric: &str) -> Self {
+        Self {
+            metric: metric.to_string(),
+            available: true,
+            reason: None,
+        }
+    }
+
+    pub fn partial(metric: &str, reason: String) -> Self {
+        Self {
+            metric: metric.to_string(),
+            available: true,
+            reason: Some(reason),
+        }
+    }
+
+    pub fn unavailable(metric: &str, reason: String) -> Self {
+        Self {
+            metric: metric.to_string(),
+            available: false,
+            reason: Some(reason),
+        }
+    }
+
+    pub fn metric(&self) -> &str {
+        &self.metric
+    }
+
+    pub fn is_available(&self) -> bool {
+        self.available
+    }
+
+    pub fn reason(&self) -> Option<&str> {
+        self.reason.as_deref()
+    }
+}
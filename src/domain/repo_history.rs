@@ -0,0 +1,55 @@
+use chrono::{DateTime, Utc};
+
+/// The user's own history in a single repository, as read from a local clone
+#[derive(Debug, Clone)]
+pub struct RepoHistory {
+    name: String,
+    added: i64,
+    deleted: i64,
+    /// Author timestamps of the user's commits, newest first
+    commits: Vec<DateTime<Utc>>,
+    /// Lowercased emails of the commits' authors
+    authors: Vec<String>,
+}
+
+impl RepoHistory {
+    pub fn new(
+        name: String,
+        added: i64,
+        deleted: i64,
+        commits: Vec<DateTime<Utc>>,
+        authors: Vec<String>,
+    ) -> Self {
+        Self {
+            name,
+            added,
+            deleted,
+            commits,
+            authors,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn added(&self) -> i64 {
+        self.added
+    }
+
+    pub fn deleted(&self) -> i64 {
+        self.deleted
+    }
+
+    pub fn commits(&self) -> &[DateTime<Utc>] {
+        &self.commits
+    }
+
+    pub fn authors(&self) -> &[String] {
+        &self.authors
+    }
+
+    pub fn commit_count(&self) -> usize {
+        self.commits.len()
+    }
+}
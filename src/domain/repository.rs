@@ -0,0 +1,459 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+
+use super::Language;
+
+/// Which repository list a repository was found in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RepoSource {
+    Owned,
+    ContributedTo,
+    Organization,
+}
+
+/// A repository as seen by the stats collector, independent of the GraphQL
+/// query it was fetched with.
+#[derive(Debug, Clone)]
+pub struct Repository {
+    id: String,
+    name_with_owner: String,
+    stargazers: i64,
+    forks: i64,
+    is_fork: bool,
+    is_archived: bool,
+    is_private: bool,
+    is_in_organization: bool,
+    pushed_at: Option<DateTime<Utc>>,
+    languages: Vec<Language>,
+    sources: Vec<RepoSource>,
+}
+
+impl Repository {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        id: String,
+        name_with_owner: String,
+        stargazers: i64,
+        forks: i64,
+        is_fork: bool,
+        is_archived: bool,
+        is_private: bool,
+        is_in_organization: bool,
+        pushed_at: Option<DateTime<Utc>>,
+        languages: Vec<Language>,
+        source: RepoSource,
+    ) -> Self {
+        Self {
+            id,
+            name_with_owner,
+            stargazers,
+            forks,
+            is_fork,
+            is_archived,
+            is_private,
+            is_in_organization,
+            pushed_at,
+            languages,
+            sources: vec![source],
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn name_with_owner(&self) -> &str {
+        &self.name_with_owner
+    }
+
+    pub fn stargazers(&self) -> i64 {
+        self.stargazers
+    }
+
+    pub fn forks(&self) -> i64 {
+        self.forks
+    }
+
+    pub fn is_fork(&self) -> bool {
+        self.is_fork
+    }
+
+    pub fn is_archived(&self) -> bool {
+        self.is_archived
+    }
+
+    pub fn is_private(&self) -> bool {
+        self.is_private
+    }
+
+    pub fn is_in_organization(&self) -> bool {
+        self.is_in_organization
+    }
+
+    pub fn pushed_at(&self) -> Option<DateTime<Utc>> {
+        self.pushed_at
+    }
+
+    pub fn languages(&self) -> &[Language] {
+        &self.languages
+    }
+
+    /// Replaces the languages, for providers that report them separately
+    pub fn set_languages(&mut self, languages: Vec<Language>) {
+        self.languages = languages;
+    }
+
+    pub fn sources(&self) -> &[RepoSource] {
+        &self.sources
+    }
+
+    fn add_source(&mut self, source: RepoSource) {
+        if !self.sources.contains(&source) {
+            self.sources.push(source);
+        }
+    }
+}
+
+/// The canonical set of repositories, each counted once no matter how many
+/// lists it was found in. Repositories are keyed by node ID and `nameWithOwner`.
+#[derive(Debug, Default)]
+pub struct RepositorySet {
+    repos: Vec<Repository>,
+    keys: HashMap<String, usize>,
+}
+
+impl RepositorySet {
+    pub fn insert(&mut self, repo: Repository) {
+        let name_key = repo.name_with_owner().to_lowercase();
+        let existing = self
+            .keys
+            .get(repo.id())
+            .or_else(|| self.keys.get(&name_key))
+            .copied();
+
+        match existing {
+            Some(idx) => {
+                for source in repo.sources() {
+                    self.repos[idx].add_source(*source);
+                }
+            }
+            None => {
+                let idx = self.repos.len();
+                if !repo.id().is_empty() {
+                    self.keys.insert(repo.id().to_string(), idx);
+                }
+                self.keys.insert(name_key, idx);
+                self.repos.push(repo);
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.repos.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.repos.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Repository> {
+        self.repos.iter()
+    }
+}
+
+impl Extend<Repository> for RepositorySet {
+    fn extend<T: IntoIterator<Item = Repository>>(&mut self, iter: T) {
+        iter.into_iter().for_each(|repo| self.insert(repo));
+    }
+}
+
+impl IntoIterator for RepositorySet {
+    type Item = Repository;
+    type IntoIter = std::vec::IntoIter<Repository>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.repos.into_iter()
+    }
+}
+
+/// Decides which repositories take part in the statistics.
+/// Every metric derived from the repository set goes through the same filter.
+#[derive(Debug, Clone, Default)]
+pub struct RepoFilter {
+    excluded: Vec<String>,
+    exclude_forks: bool,
+    exclude_archived: bool,
+    exclude_private: bool,
+    exclude_org_owned: bool,
+    min_stars: i64,
+    pushed_after: Option<DateTime<Utc>>,
+}
+
+impl RepoFilter {
+    pub fn new(excluded: Vec<String>) -> Self {
+        Self {
+            excluded,
+            ..Default::default()
+        }
+    }
+
+    pub fn exclude_forks(mut self, exclude: bool) -> Self {
+        self.exclude_forks = exclude;
+        self
+    }
+
+    pub fn exclude_archived(mut self, exclude: bool) -> Self {
+        self.exclude_archived = exclude;
+        self
+    }
+
+    pub fn exclude_private(mut self, exclude: bool) -> Self {
+        self.exclude_private = exclude;
+        self
+    }
+
+    pub fn exclude_org_owned(mut self, exclude: bool) -> Self {
+        self.exclude_org_owned = exclude;
+        self
+    }
+
+    pub fn min_stars(mut self, min_stars: i64) -> Self {
+        self.min_stars = min_stars;
+        self
+    }
+
+    pub fn pushed_after(mut self, pushed_after: Option<DateTime<Utc>>) -> Self {
+        self.pushed_after = pushed_after;
+        self
+    }
+
+    pub fn matches(&self, repo: &Repository) -> bool {
+        let excluded = self
+            .excluded
+            .iter()
+            .any(|name| name.eq_ignore_ascii_case(repo.name_with_owner()));
+        // Repositories that were never pushed to can't satisfy a push cutoff
+        let pushed_recently = self
+            .pushed_after
+            .is_none_or(|cutoff| repo.pushed_at().is_some_and(|pushed| pushed >= cutoff));
+
+        let dropped = excluded
+            || (self.exclude_forks && repo.is_fork())
+            || (self.exclude_archived && repo.is_archived())
+            || (self.exclude_private && repo.is_private())
+            || (self.exclude_org_owned && repo.is_in_organization());
+
+        !dropped && repo.stargazers() >= self.min_stars && pushed_recently
+    }
+}
+
+/// Sums the language sizes of `repositories`, largest first
+pub fn top_languages(repositories: &[Repository]) -> Vec<(String, Language)> {
+    rank_languages(repositories.iter().flat_map(Repository::languages).cloned())
+}
+
+/// Sums the sizes of languages sharing a name, largest first
+pub fn rank_languages(languages: impl IntoIterator<Item = Language>) -> Vec<(String, Language)> {
+    let mut languages_map = languages.into_iter().fold(
+        HashMap::new(),
+        |mut acc: HashMap<String, Language>, lang| {
+            acc.entry(lang.name().to_string())
+                .and_modify(|e: &mut Language| {
+                    let new_zise: i64 = e.size() + lang.size();
+                    let new_occurences: i64 = e.occurrences() + lang.occurrences();
+                    e.set_occurrences(new_occurences);
+                    e.set_size(new_zise);
+                })
+                .or_insert(lang);
+            acc
+        },
+    );
+
+    let total_size = languages_map.values().map(|lang| lang.size()).sum::<i64>();
+    languages_map.iter_mut().for_each(|(_, lang)| {
+        lang.set_proportion(total_size);
+    });
+
+    let mut languages = languages_map.into_iter().collect::<Vec<_>>();
+    languages.sort_by_key(|b| std::cmp::Reverse(b.1.size()));
+    languages
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn repo(id: &str, name_with_owner: &str, source: RepoSource) -> Repository {
+        Repository::new(
+            id.to_string(),
+            name_with_owner.to_string(),
+            0,
+            0,
+            false,
+            false,
+            false,
+            false,
+            None,
+            vec![],
+            source,
+        )
+    }
+
+    #[test]
+    fn copies_with_the_same_node_id_are_counted_once() {
+        let mut set = RepositorySet::default();
+
+        set.insert(repo("R_1", "jane/stats", RepoSource::Owned));
+        // renamed since the other list was fetched
+        set.insert(repo("R_1", "jane/github-stats", RepoSource::Owned));
+
+        assert_eq!(set.len(), 1);
+        assert_eq!(set.iter().next().unwrap().name_with_owner(), "jane/stats");
+    }
+
+    #[test]
+    fn copies_with_the_same_name_in_any_case_are_counted_once() {
+        let mut set = RepositorySet::default();
+
+        set.insert(repo("", "Jane/Stats", RepoSource::Owned));
+        set.insert(repo("", "jane/stats", RepoSource::Owned));
+        set.insert(repo("R_2", "jane/other", RepoSource::Owned));
+
+        let names = set
+            .iter()
+            .map(Repository::name_with_owner)
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["Jane/Stats", "jane/other"]);
+    }
+
+    #[test]
+    fn owned_and_contributed_copies_merge_their_sources() {
+        let mut set = RepositorySet::default();
+
+        set.extend([
+            repo("R_1", "jane/stats", RepoSource::Owned),
+            repo("R_1", "jane/stats", RepoSource::ContributedTo),
+            repo("R_1", "jane/stats", RepoSource::Owned),
+        ]);
+
+        let repos = set.into_iter().collect::<Vec<_>>();
+        assert_eq!(repos.len(), 1);
+        assert_eq!(
+            repos[0].sources(),
+            [RepoSource::Owned, RepoSource::ContributedTo]
+        );
+    }
+
+    #[test]
+    fn each_filter_drops_only_the_repositories_it_names() {
+        let cutoff = "2026-01-01T00:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        let plain = Repository {
+            stargazers: 5,
+            pushed_at: Some(cutoff),
+            ..repo("R_1", "jane/stats", RepoSource::Owned)
+        };
+        let cases = [
+            (
+                "excluded by name, in any case",
+                RepoFilter::new(vec!["JANE/Stats".to_string()]),
+                plain.clone(),
+                false,
+            ),
+            (
+                "fork",
+                RepoFilter::default().exclude_forks(true),
+                Repository {
+                    is_fork: true,
+                    ..plain.clone()
+                },
+                false,
+            ),
+            (
+                "archived",
+                RepoFilter::default().exclude_archived(true),
+                Repository {
+                    is_archived: true,
+                    ..plain.clone()
+                },
+                false,
+            ),
+            (
+                "private",
+                RepoFilter::default().exclude_private(true),
+                Repository {
+                    is_private: true,
+                    ..plain.clone()
+                },
+                false,
+            ),
+            (
+                "organization-owned",
+                RepoFilter::default().exclude_org_owned(true),
+                Repository {
+                    is_in_organization: true,
+                    ..plain.clone()
+                },
+                false,
+            ),
+            (
+                "below the minimum stars",
+                RepoFilter::default().min_stars(6),
+                plain.clone(),
+                false,
+            ),
+            (
+                "at the minimum stars",
+                RepoFilter::default().min_stars(5),
+                plain.clone(),
+                true,
+            ),
+            (
+                "pushed before the cutoff",
+                RepoFilter::default().pushed_after(Some(cutoff + chrono::TimeDelta::seconds(1))),
+                plain.clone(),
+                false,
+            ),
+            (
+                "pushed at the cutoff",
+                RepoFilter::default().pushed_after(Some(cutoff)),
+                plain.clone(),
+                true,
+            ),
+            (
+                "never pushed, with a cutoff",
+                RepoFilter::default().pushed_after(Some(cutoff)),
+                Repository {
+                    pushed_at: None,
+                    ..plain.clone()
+                },
+                false,
+            ),
+            (
+                "never pushed, without a cutoff",
+                RepoFilter::default(),
+                Repository {
+                    pushed_at: None,
+                    ..plain.clone()
+                },
+                true,
+            ),
+            (
+                "fork, archived, private and organization-owned without filters",
+                RepoFilter::default(),
+                Repository {
+                    is_fork: true,
+                    is_archived: true,
+                    is_private: true,
+                    is_in_organization: true,
+                    ..plain.clone()
+                },
+                true,
+            ),
+        ];
+
+        for (case, filter, repo, kept) in cases {
+            assert_eq!(filter.matches(&repo), kept, "{case}");
+        }
+    }
+}
@@ -1,22 +1,58 @@
+use std::collections::{BTreeMap, HashSet};
+
+use anyhow::Result;
+
 use derive_builder::Builder;
 
-use super::contribution_calendar::ContributionCalendarUserContributionsCollectionContributionCalendarWeeks;
+use super::{
+    calendar_from_counts, rank_languages, top_languages, ContributionWeek, DailyViews, Metric,
+    RepoHistory, Repository,
+};
 
-pub type CalendarWeek = ContributionCalendarUserContributionsCollectionContributionCalendarWeeks;
+/// Lines added and deleted, per repository
+pub type LinesChangedByRepo = Vec<(String, (i64, i64))>;
 
-#[derive(Builder)]
+#[derive(Clone, Builder)]
 pub struct Stats {
     name: String,
+    /// Only set for organization statistics
+    #[builder(default)]
+    avatar_url: Option<String>,
+    /// Whether the statistics describe an organization rather than a user
+    #[builder(default)]
+    organization: bool,
     stargazers: i64,
     forks: i64,
-    total_contributions: i64,
+    #[builder(setter(into))]
+    total_contributions: Metric<i64>,
     /// A Vec of languages with their name as key and the Language struct as value
-    /// Sorted by the size of the language
+    /// Sorted by the size of the language, every language and not only the ones on the cards
     languages: Vec<(String, Language)>,
-    repos: Vec<String>,
-    lines_changed: (i64, i64),
-    views: i64,
-    contribution_calendar: Vec<CalendarWeek>,
+    /// How many of the languages the cards show
+    #[builder(default = "usize::MAX")]
+    languages_limit: usize,
+    /// Deduplicated repositories the repository-based metrics were computed from
+    repos: Vec<Repository>,
+    /// Lines added and deleted by the user, per repository
+    #[builder(setter(into))]
+    lines_changed_by_repo: Metric<LinesChangedByRepo>,
+    /// Daily views of each repository, as far back as the forge keeps them
+    #[builder(setter(into))]
+    views: Metric<Vec<DailyViews>>,
+    /// Pull request reviews over the past year, unavailable unless the forge reports them
+    #[builder(
+        default = "Metric::Unavailable(\"the forge doesn't report reviews\".to_string())",
+        setter(into)
+    )]
+    reviews: Metric<i64>,
+    /// Distinct authors across all repositories, only set for organization statistics
+    #[builder(default)]
+    contributors: Option<i64>,
+    /// Per-repository commit history, only collected by the local git backend
+    #[builder(default)]
+    history: Vec<RepoHistory>,
+    #[builder(setter(into))]
+    contribution_calendar: Metric<Vec<ContributionWeek>>,
 }
 
 impl Stats {
@@ -24,6 +60,22 @@ impl Stats {
         &self.name
     }
 
+    pub fn avatar_url(&self) -> Option<&str> {
+        self.avatar_url.as_deref()
+    }
+
+    pub fn is_organization(&self) -> bool {
+        self.organization
+    }
+
+    pub fn reviews(&self) -> Option<i64> {
+        self.reviews.value().copied()
+    }
+
+    pub fn contributors(&self) -> Option<i64> {
+        self.contributors
+    }
+
     pub fn stargazers(&self) -> i64 {
         self.stargazers
     }
@@ -32,51 +84,319 @@ impl Stats {
         self.forks
     }
 
-    pub fn total_contributions(&self) -> i64 {
-        self.total_contributions
+    pub fn total_contributions(&self) -> Option<i64> {
+        self.total_contributions.value().copied()
     }
 
-    pub fn languages(&self) -> &Vec<(String, Language)> {
+    /// The largest languages, as many as the cards show
+    pub fn languages(&self) -> &[(String, Language)] {
+        &self.languages[..self.languages_limit.min(self.languages.len())]
+    }
+
+    /// Every language, for exports that shouldn't lose the long tail
+    pub fn all_languages(&self) -> &[(String, Language)] {
         &self.languages
     }
 
-    pub fn repos(&self) -> &Vec<String> {
-        self.repos.as_ref()
+    pub fn repos(&self) -> &[Repository] {
+        &self.repos
+    }
+
+    pub fn lines_changed(&self) -> Option<(i64, i64)> {
+        self.lines_changed_by_repo().map(|by_repo| {
+            by_repo.iter().fold((0, 0), |acc, (_, (added, deleted))| {
+                (acc.0 + added, acc.1 + deleted)
+            })
+        })
+    }
+
+    pub fn lines_changed_by_repo(&self) -> Option<&[(String, (i64, i64))]> {
+        self.lines_changed_by_repo.value().map(Vec::as_slice)
+    }
+
+    pub fn views(&self) -> Option<i64> {
+        self.traffic()
+            .map(|traffic| traffic.iter().map(DailyViews::count).sum())
+    }
+
+    pub fn traffic(&self) -> Option<&[DailyViews]> {
+        self.views.value().map(Vec::as_slice)
+    }
+
+    pub fn history(&self) -> &[RepoHistory] {
+        &self.history
+    }
+
+    pub fn commit_count(&self) -> usize {
+        self.history.iter().map(RepoHistory::commit_count).sum()
+    }
+
+    pub fn contribution_calendar(&self) -> Option<&[ContributionWeek]> {
+        self.contribution_calendar.value().map(Vec::as_slice)
     }
 
-    pub fn lines_changed(&self) -> (i64, i64) {
-        self.lines_changed
+    /// The metrics that couldn't be collected, with the reason why
+    pub fn unavailable(&self) -> Vec<(&'static str, &str)> {
+        self.reasons()
+            .into_iter()
+            .filter_map(|(metric, reason)| reason.map(|reason| (metric, reason)))
+            .collect()
     }
 
-    pub fn views(&self) -> i64 {
-        self.views
+    /// Every metric that may be unavailable, with whether it was collected
+    pub fn availability(&self) -> Vec<(&'static str, bool)> {
+        self.reasons()
+            .into_iter()
+            .map(|(metric, reason)| (metric, reason.is_none()))
+            .collect()
+    }
+
+    fn reasons(&self) -> [(&'static str, Option<&str>); 5] {
+        [
+            ("Contributions", self.total_contributions.reason()),
+            ("Lines changed", self.lines_changed_by_repo.reason()),
+            ("Repository views", self.views.reason()),
+            ("Reviews", self.reviews.reason()),
+            ("Contribution calendar", self.contribution_calendar.reason()),
+        ]
+    }
+
+    /// Replaces the repositories, recomputing the stars, forks and languages derived from them
+    pub fn set_repos(&mut self, repos: Vec<Repository>) {
+        self.stargazers = repos.iter().map(Repository::stargazers).sum();
+        self.forks = repos.iter().map(Repository::forks).sum();
+        self.languages = top_languages(&repos);
+        self.repos = repos;
+    }
+
+    /// Replaces the lines changed in one repository, left as is while the metric is unavailable
+    pub fn set_lines_changed(&mut self, repo: &str, changed: (i64, i64)) {
+        if let Metric::Available(by_repo) = &mut self.lines_changed_by_repo {
+            match by_repo
+                .iter_mut()
+                .find(|(name, _)| name.eq_ignore_ascii_case(repo))
+            {
+                Some((_, existing)) => *existing = changed,
+                None => by_repo.push((repo.to_string(), changed)),
+            }
+        }
+    }
+
+    /// Replaces the history of one repository along with its lines changed
+    pub fn set_repo_history(&mut self, history: RepoHistory) {
+        self.set_lines_changed(history.name(), (history.added(), history.deleted()));
+        match self
+            .history
+            .iter_mut()
+            .find(|existing| existing.name() == history.name())
+        {
+            Some(existing) => *existing = history,
+            None => self.history.push(history),
+        }
+    }
+
+    pub fn set_total_contributions(&mut self, total_contributions: Metric<i64>) {
+        self.total_contributions = total_contributions;
+    }
+
+    pub fn set_reviews(&mut self, reviews: Metric<i64>) {
+        self.reviews = reviews;
+    }
+
+    pub fn set_contribution_calendar(&mut self, calendar: Metric<Vec<ContributionWeek>>) {
+        self.contribution_calendar = calendar;
+    }
+
+    /// Combines the statistics of several accounts, possibly on different forges,
+    /// into one. Each part is paired with the forge it comes from, e.g. its URL.
+    /// The name and avatar are taken from the first account that has them, and the
+    /// result describes an organization when any account does.
+    ///
+    /// Repositories are deduplicated by forge and `nameWithOwner` since IDs are only
+    /// unique within one forge; stars, forks and languages are recomputed from the
+    /// remaining ones. Contribution calendars are summed day by day.
+    ///
+    /// Languages are summed in bytes when every forge reports bytes. Otherwise each
+    /// account's languages are turned into shares first, so sizes in different units
+    /// are never added up.
+    pub fn merge(parts: Vec<(String, Stats)>, languages_limit: usize) -> Result<Stats> {
+        if parts.is_empty() {
+            anyhow::bail!("No statistics to merge");
+        }
+        let name = parts
+            .iter()
+            .map(|(_, part)| part.name.clone())
+            .find(|name| !name.is_empty())
+            .unwrap_or_default();
+        let avatar_url = parts.iter().find_map(|(_, part)| part.avatar_url.clone());
+        let organization = parts.iter().any(|(_, part)| part.organization);
+
+        let mut seen = HashSet::new();
+        let mut seen_views = HashSet::new();
+        let mut repos = vec![];
+        let mut repos_by_part = vec![];
+        let mut lines_changed_by_repo = Metric::Available(LinesChangedByRepo::new());
+        let mut counts = Metric::Available(BTreeMap::new());
+        let mut history = vec![];
+        let mut contributors = None;
+        let mut total_contributions = Metric::Available(0);
+        let mut views = Metric::Available(vec![]);
+        let mut reviews = Metric::Available(0);
+
+        // a metric that is unavailable for one account is unavailable for all of them
+        for (forge, part) in parts {
+            let forge = forge.trim_end_matches('/').to_lowercase();
+            total_contributions =
+                total_contributions.zip_with(part.total_contributions, |a, b| a + b);
+            views = views.zip_with(part.views, |mut acc, traffic| {
+                // a repository found in several sources of the same forge is only counted once
+                acc.extend(traffic.into_iter().filter(|day| {
+                    seen_views.insert((forge.clone(), day.repository().to_lowercase(), day.date()))
+                }));
+                acc
+            });
+            reviews = reviews.zip_with(part.reviews, |a, b| a + b);
+            contributors = match (contributors, part.contributors) {
+                (Some(acc), Some(count)) => Some(acc + count),
+                (acc, count) => acc.or(count),
+            };
+
+            let kept = part
+                .repos
+                .into_iter()
+                .filter(|repo| seen.insert((forge.clone(), repo.name_with_owner().to_lowercase())))
+                .collect::<Vec<_>>();
+            repos.extend(kept.iter().cloned());
+            repos_by_part.push(kept);
+
+            lines_changed_by_repo =
+                lines_changed_by_repo.zip_with(part.lines_changed_by_repo, |mut acc, by_repo| {
+                    for (name, (added, deleted)) in by_repo {
+                        match acc
+                            .iter_mut()
+                            .find(|(existing, _)| existing.eq_ignore_ascii_case(&name))
+                        {
+                            Some((_, changed)) => {
+                                *changed = (changed.0 + added, changed.1 + deleted)
+                            }
+                            None => acc.push((name, (added, deleted))),
+                        }
+                    }
+                    acc
+                });
+
+            counts = counts.zip_with(part.contribution_calendar, |mut counts, weeks| {
+                for day in weeks.iter().flat_map(ContributionWeek::days) {
+                    *counts.entry(day.date()).or_insert(0) += day.count();
+                }
+                counts
+            });
+
+            history.extend(part.history);
+        }
+
+        let contribution_calendar = counts.map(|counts| {
+            counts
+                .last_key_value()
+                .map(|(today, _)| calendar_from_counts(&counts, *today))
+                .unwrap_or_default()
+        });
+
+        let stats = StatsBuilder::default()
+            .name(name)
+            .avatar_url(avatar_url)
+            .organization(organization)
+            .stargazers(repos.iter().map(Repository::stargazers).sum())
+            .forks(repos.iter().map(Repository::forks).sum())
+            .languages(merge_languages(&repos_by_part))
+            .languages_limit(languages_limit)
+            .total_contributions(total_contributions)
+            .views(views)
+            .reviews(reviews)
+            .contributors(contributors)
+            .lines_changed_by_repo(lines_changed_by_repo)
+            .history(history)
+            .repos(repos)
+            .contribution_calendar(contribution_calendar)
+            .build()?;
+
+        Ok(stats)
     }
+}
 
-    pub fn contribution_calendar(&self) -> &[CalendarWeek] {
-        &self.contribution_calendar
+/// Ranks the languages of each account's repositories, in bytes when every one of them
+/// is measured in bytes, by each account's shares otherwise
+fn merge_languages(repos_by_part: &[Vec<Repository>]) -> Vec<(String, Language)> {
+    let all_bytes = repos_by_part
+        .iter()
+        .flatten()
+        .flat_map(Repository::languages)
+        .all(|language| language.size_unit() == SizeUnit::Bytes);
+    if all_bytes {
+        return top_languages(&repos_by_part.concat());
     }
+
+    let shares = repos_by_part.iter().flat_map(|repos| {
+        top_languages(repos).into_iter().map(|(name, language)| {
+            let mut share =
+                Language::share(name, language.proportion(), language.color().to_string());
+            share.set_occurrences(language.occurrences());
+            share
+        })
+    });
+    rank_languages(shares)
 }
 
 #[derive(Debug, Clone)]
 pub struct Language {
     name: String,
     size: i64,
+    size_unit: SizeUnit,
     occurrences: i64,
     color: String,
     proportion: f64,
 }
 
+/// What a language's `size` measures
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SizeUnit {
+    Bytes,
+    /// Hundredths of a percent of the repository, for forges that only report shares
+    BasisPoints,
+}
+
 impl Language {
     pub fn new(name: String, size: i64, occurrences: i64, color: String, proportion: f64) -> Self {
         Self {
             name,
             size,
+            size_unit: SizeUnit::Bytes,
             occurrences,
             color,
             proportion,
         }
     }
 
+    /// A language known only by its share of a repository, e.g. `12.5` percent
+    pub fn share(name: String, percentage: f64, color: String) -> Self {
+        Self {
+            name,
+            size: (percentage * 100.0).round() as i64,
+            size_unit: SizeUnit::BasisPoints,
+            occurrences: 1,
+            color,
+            proportion: 0.0,
+        }
+    }
+
+    /// For forges that don't report language colors, derives a stable one from the name
+    pub fn fallback_color(name: &str) -> String {
+        let hash = name.bytes().fold(0u32, |acc, byte| {
+            acc.wrapping_mul(31).wrapping_add(byte as u32)
+        });
+        format!("#{:06x}", hash & 0xffffff)
+    }
+
     pub fn color(&self) -> &str {
         &self.color
     }
@@ -101,6 +421,15 @@ impl Language {
         self.size
     }
 
+    pub fn size_unit(&self) -> SizeUnit {
+        self.size_unit
+    }
+
+    /// The size in bytes, `None` when the forge only reported a share
+    pub fn bytes(&self) -> Option<i64> {
+        (self.size_unit == SizeUnit::Bytes).then_some(self.size)
+    }
+
     pub fn occurrences(&self) -> i64 {
         self.occurrences
     }
@@ -110,3 +439,207 @@ impl Language {
         self.proportion = proportion;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+    use crate::domain::{ContributionDay, RepoSource, SizeUnit};
+
+    fn repo(name: &str, stars: i64, languages: Vec<Language>) -> Repository {
+        Repository::new(
+            name.to_string(),
+            name.to_string(),
+            stars,
+            1,
+            false,
+            false,
+            false,
+            false,
+            None,
+            languages,
+            RepoSource::Owned,
+        )
+    }
+
+    fn bytes(name: &str, size: i64) -> Language {
+        Language::new(name.to_string(), size, 1, "#000000".to_string(), 0.0)
+    }
+
+    fn share(name: &str, percentage: f64) -> Language {
+        Language::share(name.to_string(), percentage, "#000000".to_string())
+    }
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 10, day).unwrap()
+    }
+
+    fn stats(repos: Vec<Repository>, contributions: i64, views: Vec<DailyViews>) -> Stats {
+        StatsBuilder::default()
+            .name("jane".to_string())
+            .stargazers(repos.iter().map(Repository::stargazers).sum())
+            .forks(repos.iter().map(Repository::forks).sum())
+            .languages(top_languages(&repos))
+            .repos(repos)
+            .total_contributions(Metric::Available(contributions))
+            .lines_changed_by_repo(Metric::Available(vec![]))
+            .views(Metric::Available(views))
+            .reviews(Metric::Available(1))
+            .contribution_calendar(Metric::Available(vec![ContributionWeek::new(vec![
+                ContributionDay::new(date(18), contributions, 1),
+            ])]))
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn merge_sums_counters_and_calendars() {
+        let merged = Stats::merge(
+            vec![
+                (
+                    "https://api.github.com".to_string(),
+                    stats(vec![], 3, vec![]),
+                ),
+                ("https://gitlab.com".to_string(), stats(vec![], 4, vec![])),
+            ],
+            10,
+        )
+        .unwrap();
+
+        assert_eq!(merged.total_contributions(), Some(7));
+        assert_eq!(merged.reviews(), Some(2));
+        let days = merged
+            .contribution_calendar()
+            .unwrap()
+            .iter()
+            .flat_map(ContributionWeek::days)
+            .map(ContributionDay::count)
+            .sum::<i64>();
+        assert_eq!(days, 7);
+    }
+
+    #[test]
+    fn merge_counts_a_repository_once_per_forge() {
+        let views = |count| vec![DailyViews::new("jane/app".to_string(), date(18), count, 1)];
+        let merged = Stats::merge(
+            vec![
+                (
+                    "https://api.github.com".to_string(),
+                    stats(vec![repo("jane/app", 5, vec![])], 0, views(2)),
+                ),
+                (
+                    "https://api.github.com/".to_string(),
+                    stats(vec![repo("Jane/App", 5, vec![])], 0, views(2)),
+                ),
+                (
+                    "https://codeberg.org".to_string(),
+                    stats(vec![repo("jane/app", 1, vec![])], 0, vec![]),
+                ),
+            ],
+            10,
+        )
+        .unwrap();
+
+        assert_eq!(merged.repos().len(), 2);
+        assert_eq!(merged.stargazers(), 6);
+        assert_eq!(merged.views(), Some(2));
+    }
+
+    #[test]
+    fn merge_keeps_bytes_when_every_forge_reports_bytes() {
+        let merged = Stats::merge(
+            vec![
+                (
+                    "https://api.github.com".to_string(),
+                    stats(
+                        vec![repo("jane/app", 0, vec![bytes("Rust", 300)])],
+                        0,
+                        vec![],
+                    ),
+                ),
+                (
+                    "https://codeberg.org".to_string(),
+                    stats(vec![repo("jane/lib", 0, vec![bytes("Go", 100)])], 0, vec![]),
+                ),
+            ],
+            10,
+        )
+        .unwrap();
+
+        let (name, rust) = &merged.languages()[0];
+        assert_eq!(name, "Rust");
+        assert_eq!(rust.bytes(), Some(300));
+        assert!((rust.proportion() - 75.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn merge_weighs_sources_by_share_when_a_forge_reports_shares() {
+        // a large GitHub account in bytes and a GitLab account in percent
+        let merged = Stats::merge(
+            vec![
+                (
+                    "https://api.github.com".to_string(),
+                    stats(
+                        vec![repo(
+                            "jane/app",
+                            0,
+                            vec![bytes("Rust", 750_000), bytes("Shell", 250_000)],
+                        )],
+                        0,
+                        vec![],
+                    ),
+                ),
+                (
+                    "https://gitlab.com".to_string(),
+                    stats(
+                        vec![repo("jane/lib", 0, vec![share("Go", 100.0)])],
+                        0,
+                        vec![],
+                    ),
+                ),
+            ],
+            10,
+        )
+        .unwrap();
+
+        let proportions = merged
+            .languages()
+            .iter()
+            .map(|(name, language)| {
+                assert_eq!(language.size_unit(), SizeUnit::BasisPoints);
+                (name.as_str(), language.proportion())
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(proportions, [("Go", 50.0), ("Rust", 37.5), ("Shell", 12.5)]);
+    }
+
+    #[test]
+    fn merge_keeps_the_metadata_of_later_sources() {
+        let mut organization = stats(vec![], 0, vec![]);
+        organization.name = "acme".to_string();
+        organization.avatar_url = Some("https://example.com/acme.png".to_string());
+        organization.organization = true;
+        let mut unnamed = stats(vec![], 0, vec![]);
+        unnamed.name = String::new();
+
+        let merged = Stats::merge(
+            vec![
+                ("https://gitlab.com".to_string(), unnamed),
+                ("https://api.github.com".to_string(), organization),
+                ("https://codeberg.org".to_string(), stats(vec![], 0, vec![])),
+            ],
+            10,
+        )
+        .unwrap();
+
+        assert_eq!(merged.name(), "acme");
+        assert_eq!(merged.avatar_url(), Some("https://example.com/acme.png"));
+        assert!(merged.is_organization());
+    }
+
+    #[test]
+    fn merge_of_nothing_is_an_error() {
+        assert!(Stats::merge(vec![], 10).is_err());
+    }
+}
@@ -0,0 +1,12 @@
+/// The part of the statistics an event makes stale
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum StatsChange {
+    /// Stars, forks, languages and the repository list
+    Repositories,
+    /// Lines changed in one repository, by `nameWithOwner`
+    LinesChanged(String),
+    /// Total contributions, reviews and the contribution calendar
+    Contributions,
+    /// Anything that doesn't map to a single part, everything is collected again
+    Everything,
+}
@@ -0,0 +1,175 @@
+use std::str::FromStr;
+
+use serde::Deserialize;
+
+use super::Stats;
+
+/// Color of a badge's value, unless the metric has one of its own
+const DEFAULT_COLOR: &str = "#007ec6";
+
+/// A single value derived from `Stats`, shown on badges and custom cards
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StatsMetric {
+    Stars,
+    Forks,
+    Contributions,
+    LinesChanged,
+    Views,
+    Reviews,
+    Repositories,
+    TopLanguage,
+}
+
+impl StatsMetric {
+    pub const ALL: [StatsMetric; 8] = [
+        StatsMetric::Stars,
+        StatsMetric::Forks,
+        StatsMetric::Contributions,
+        StatsMetric::LinesChanged,
+        StatsMetric::Views,
+        StatsMetric::Reviews,
+        StatsMetric::Repositories,
+        StatsMetric::TopLanguage,
+    ];
+
+    /// How the metric is named in the configuration and in a badge's file name
+    pub fn name(&self) -> &'static str {
+        match self {
+            StatsMetric::Stars => "stars",
+            StatsMetric::Forks => "forks",
+            StatsMetric::Contributions => "contributions",
+            StatsMetric::LinesChanged => "lines_changed",
+            StatsMetric::Views => "views",
+            StatsMetric::Reviews => "reviews",
+            StatsMetric::Repositories => "repositories",
+            StatsMetric::TopLanguage => "top_language",
+        }
+    }
+
+    /// Text on the left side of a badge, or of a card's row
+    pub fn label(&self) -> &'static str {
+        match self {
+            StatsMetric::LinesChanged => "lines changed",
+            StatsMetric::TopLanguage => "top language",
+            _ => self.name(),
+        }
+    }
+
+    /// The value shown, `None` when the metric is unavailable.
+    /// Counters that grow fast are abbreviated, the others keep every digit.
+    pub fn value(&self, stats: &Stats) -> Option<String> {
+        match self {
+            StatsMetric::Stars => Some(compact(stats.stargazers())),
+            StatsMetric::Forks => Some(compact(stats.forks())),
+            StatsMetric::Contributions => stats.total_contributions().map(grouped),
+            StatsMetric::LinesChanged => stats
+                .lines_changed()
+                .map(|(added, deleted)| compact(added + deleted)),
+            StatsMetric::Views => stats.views().map(compact),
+            StatsMetric::Reviews => stats.reviews().map(grouped),
+            StatsMetric::Repositories => Some(grouped(stats.repos().len() as i64)),
+            StatsMetric::TopLanguage => stats.languages().first().map(|(name, _)| name.clone()),
+        }
+    }
+
+    /// Background of a badge's value, the language's color for the top language
+    pub fn color(&self, stats: &Stats) -> String {
+        match self {
+            StatsMetric::TopLanguage => stats
+                .languages()
+                .first()
+                .map(|(_, language)| language.color().to_string())
+                .unwrap_or_else(|| DEFAULT_COLOR.to_string()),
+            _ => DEFAULT_COLOR.to_string(),
+        }
+    }
+}
+
+impl FromStr for StatsMetric {
+    type Err = anyhow::Error;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        StatsMetric::ALL
+            .into_iter()
+            .find(|metric| metric.name() == name)
+            .ok_or_else(|| {
+                let names = StatsMetric::ALL.map(|metric| metric.name());
+                anyhow::anyhow!(
+                    "Unknown metric {name}, expected one of {}",
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/// `1234` as `1.2k`, `5600000` as `5.6M`
+pub fn compact(value: i64) -> String {
+    const UNITS: [(u64, &str); 3] = [(1_000_000_000, "B"), (1_000_000, "M"), (1_000, "k")];
+
+    UNITS
+        .iter()
+        // values rounding up to the next unit are shown in it, `999999` as `1M` rather than `1000k`
+        .find(|(size, _)| value.unsigned_abs() >= size - size / 20_000)
+        .map(|(size, unit)| {
+            let scaled = format!("{:.1}", value as f64 / *size as f64);
+            format!("{}{unit}", scaled.trim_end_matches(".0"))
+        })
+        .unwrap_or_else(|| value.to_string())
+}
+
+/// `3456` as `3,456`
+fn grouped(value: i64) -> String {
+    let digits = value.unsigned_abs().to_string();
+    let mut grouped = String::new();
+    for (idx, digit) in digits.chars().enumerate() {
+        if idx > 0 && (digits.len() - idx).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    if value < 0 {
+        grouped.insert(0, '-');
+    }
+    grouped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compact_switches_units_at_their_boundaries() {
+        for (value, expected) in [
+            (0, "0"),
+            (999, "999"),
+            (1_000, "1k"),
+            (1_234, "1.2k"),
+            (999_949, "999.9k"),
+            (999_999, "1M"),
+            (1_000_000, "1M"),
+            (5_600_000, "5.6M"),
+            (999_999_999, "1B"),
+            (-999, "-999"),
+            (-1_500, "-1.5k"),
+            (-999_999, "-1M"),
+        ] {
+            assert_eq!(compact(value), expected, "{value}");
+        }
+    }
+
+    #[test]
+    fn grouped_separates_thousands() {
+        for (value, expected) in [
+            (0, "0"),
+            (999, "999"),
+            (1_000, "1,000"),
+            (3_456, "3,456"),
+            (1_000_000, "1,000,000"),
+            (-3_456, "-3,456"),
+            (-999, "-999"),
+        ] {
+            assert_eq!(grouped(value), expected, "{value}");
+        }
+    }
+}
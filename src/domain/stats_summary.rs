@@ -0,0 +1,76 @@
+use serde::Serialize;
+
+use super::Stats;
+
+/// The statistics as published over HTTP, unavailable metrics are `null`
+#[derive(Debug, Serialize)]
+pub struct StatsSummary {
+    name: String,
+    avatar_url: Option<String>,
+    stargazers: i64,
+    forks: i64,
+    repositories: usize,
+    total_contributions: Option<i64>,
+    lines_changed: Option<LinesChanged>,
+    views: Option<i64>,
+    reviews: Option<i64>,
+    contributors: Option<i64>,
+    languages: Vec<LanguageShare>,
+    unavailable: Vec<UnavailableMetric>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LinesChanged {
+    added: i64,
+    deleted: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LanguageShare {
+    name: String,
+    color: String,
+    /// Percentage of the code in this language
+    proportion: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UnavailableMetric {
+    metric: String,
+    reason: String,
+}
+
+impl From<&Stats> for StatsSummary {
+    fn from(stats: &Stats) -> Self {
+        Self {
+            name: stats.name().to_string(),
+            avatar_url: stats.avatar_url().map(str::to_string),
+            stargazers: stats.stargazers(),
+            forks: stats.forks(),
+            repositories: stats.repos().len(),
+            total_contributions: stats.total_contributions(),
+            lines_changed: stats
+                .lines_changed()
+                .map(|(added, deleted)| LinesChanged { added, deleted }),
+            views: stats.views(),
+            reviews: stats.reviews(),
+            contributors: stats.contributors(),
+            languages: stats
+                .languages()
+                .iter()
+                .map(|(name, language)| LanguageShare {
+                    name: name.clone(),
+                    color: language.color().to_string(),
+                    proportion: language.proportion(),
+                })
+                .collect(),
+            unavailable: stats
+                .unavailable()
+                .into_iter()
+                .map(|(metric, reason)| UnavailableMetric {
+                    metric: metric.to_string(),
+                    reason: reason.to_string(),
+                })
+                .collect(),
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize)]
@@ -25,4 +25,50 @@ impl View {
     pub fn count(&self) -> i64 {
         self.count
     }
+
+    pub fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    pub fn uniques(&self) -> i64 {
+        self.uniques
+    }
+}
+
+/// Views of one repository on one day
+#[derive(Debug, Clone)]
+pub struct DailyViews {
+    repository: String,
+    date: NaiveDate,
+    count: i64,
+    uniques: i64,
+}
+
+impl DailyViews {
+    pub fn new(repository: String, date: NaiveDate, count: i64, uniques: i64) -> Self {
+        Self {
+            repository,
+            date,
+            count,
+            uniques,
+        }
+    }
+
+    /// Repository the views are of, by `nameWithOwner`
+    pub fn repository(&self) -> &str {
+        &self.repository
+    }
+
+    pub fn date(&self) -> NaiveDate {
+        self.date
+    }
+
+    pub fn count(&self) -> i64 {
+        self.count
+    }
+
+    /// Unique visitors
+    pub fn uniques(&self) -> i64 {
+        self.uniques
+    }
 }
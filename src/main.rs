@@ -1,51 +1,350 @@
 use github_stats_rs::{
-    algebra::{GithubExt, ImageGenExt},
-    service::{Configuration, Github, ImageGen, Telemetry},
+    algebra::{Forge, GithubExt, ImageGenExt},
+    domain::{Stats, StatsChange, StatsError},
+    service::{
+        offline_stats, write_metrics_textfile, Auth, Configuration, CsvExporter, Gitea, Github,
+        Gitlab, ImageGen, Mode, Provider, ReadmeUpdater, ResponseCache, StatsLoader,
+        StatsRefresher, StatsServer, Telemetry,
+    },
 };
-use reqwest::Client;
-use secrecy::ExposeSecret;
-use std::time::Duration;
+use reqwest::{Certificate, Client};
+use std::{process::ExitCode, sync::Arc, time::Duration};
 use tracing_subscriber::Registry;
 
 #[tokio::main]
-async fn main() -> Result<(), anyhow::Error> {
+async fn main() -> ExitCode {
     Telemetry::<Registry>::new("github_stats_rs".into(), "info".into(), std::io::stdout).init();
 
+    match run().await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            tracing::error!("{e:?}");
+            exit_code(&e)
+        }
+    }
+}
+
+/// Distinct exit codes for the failures a CI job may want to handle differently
+fn exit_code(error: &anyhow::Error) -> ExitCode {
+    let code = match error
+        .chain()
+        .find_map(|cause| cause.downcast_ref::<StatsError>())
+    {
+        Some(StatsError::Auth { .. }) => 3,
+        Some(StatsError::RateLimited { .. }) => 4,
+        Some(StatsError::NotFound(_)) => 5,
+        Some(StatsError::Schema { .. }) => 6,
+        Some(StatsError::TemplateMissing { .. }) => 7,
+        Some(StatsError::Io(_)) => 8,
+        Some(StatsError::Http(_) | StatsError::Other(_)) | None => 1,
+    };
+    ExitCode::from(code)
+}
+
+async fn run() -> Result<(), anyhow::Error> {
     let configuration = Configuration::load_or_die();
     tracing::info!("{configuration:#?}");
 
-    let client = Client::builder()
-        .user_agent("graphql-rust")
-        .timeout(Duration::from_secs(30))
-        .connect_timeout(Duration::from_secs(10))
-        .default_headers(
-            std::iter::once((
-                reqwest::header::AUTHORIZATION,
-                reqwest::header::HeaderValue::from_str(&format!(
-                    "Bearer {}",
-                    configuration.access_token().expose_secret()
-                ))
-                .unwrap(),
-            ))
-            .collect(),
-        )
-        .build()?;
-
-    let github = Github::new(configuration.clone(), client);
-    let stats = github.get_stats().await?;
-    let lines_changed = stats.lines_changed();
-    let total_contributions = stats.total_contributions();
+    if configuration.mode() == Mode::Serve {
+        return serve(configuration).await;
+    }
+    if !configuration.team_members().is_empty() {
+        return run_team(&configuration).await;
+    }
 
+    // a typo in the badges fails the run before any crawling
+    let badges = configuration.badges()?;
+    let stats = collect_all(&configuration).await?;
     // Generate the images
     let image_gen = ImageGen::new(
         configuration.template_folder().to_string(),
         configuration.output_folder().to_string(),
-    );
+    )
+    .with_languages_chart(configuration.languages_chart());
     image_gen.generate_overview(&stats)?;
     image_gen.generate_languages(&stats)?;
     image_gen.generate_contributions_grid(&stats)?;
+    image_gen.generate_badges(&stats, &badges)?;
+    image_gen.generate_custom_cards(&stats, configuration.cards())?;
+    if configuration.dashboard() {
+        image_gen.generate_dashboard(&stats)?;
+    }
+    if configuration.csv_export() {
+        CsvExporter::new(configuration.output_folder().to_string()).export(&stats)?;
+    }
+
+    if let Some(path) = configuration.metrics_textfile() {
+        write_metrics_textfile(path, &[(configuration.account(), &stats)])?;
+        tracing::info!("Wrote the metrics to {}", path);
+    }
+
+    if let Some(path) = configuration.readme_path() {
+        let updater = ReadmeUpdater::new(
+            path.to_string(),
+            configuration.readme_image_base_url().to_string(),
+            configuration.readme_text_metrics(),
+        );
+        if updater.update(&stats)? {
+            tracing::info!("Updated the statistics in {}", path);
+        } else {
+            tracing::info!("Statistics in {} are up to date", path);
+        }
+    }
+
+    if let Some(total_contributions) = stats.total_contributions() {
+        tracing::info!("Total contributions: {}", total_contributions);
+    }
+    if let Some((added, deleted)) = stats.lines_changed() {
+        tracing::info!("Lines changed: {}, {}", added, deleted);
+    }
+    for (metric, reason) in stats.unavailable() {
+        tracing::warn!("{} shown as unavailable: {}", metric, reason);
+    }
+    Ok(())
+}
+
+/// Serves the cards over HTTP until the process is asked to stop
+async fn serve(configuration: Configuration) -> Result<(), anyhow::Error> {
+    let image_gen = ImageGen::new(
+        configuration.template_folder().to_string(),
+        configuration.output_folder().to_string(),
+    )
+    .with_languages_chart(configuration.languages_chart());
+    let loader: StatsLoader =
+        Arc::new(|configuration| Box::pin(async move { collect_all(&configuration).await }));
+    let refresher: StatsRefresher = Arc::new(|configuration, stats, changes| {
+        Box::pin(async move { refresh_all(&configuration, &stats, &changes).await })
+    });
+
+    StatsServer::new(configuration, image_gen, loader, refresher)
+        .serve(shutdown_signal())
+        .await
+}
+
+/// Completes on Ctrl+C or SIGTERM
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::error!("Failed to listen for Ctrl+C: {:?}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(e) => {
+                tracing::error!("Failed to listen for SIGTERM: {:?}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+    tracing::info!("Shutting down, waiting for in-flight requests");
+}
+
+/// Collects the configured account's statistics, merging every source when several are configured
+async fn collect_all(configuration: &Configuration) -> Result<Stats, anyhow::Error> {
+    let cache = ResponseCache::default();
+    if configuration.sources().is_empty() {
+        return collect_from(configuration, &cache).await;
+    }
+
+    let mut parts = vec![];
+    for source in configuration.sources() {
+        let configuration = configuration.for_source(source)?;
+        parts.push((
+            configuration.forge_url(),
+            collect_from(&configuration, &cache).await?,
+        ));
+    }
+    Stats::merge(parts, configuration.languages_limit())
+}
+
+/// Collects the statistics of the account `configuration` points to, sharing
+/// GitHub responses through `cache` with the other accounts of the run
+async fn collect_from(
+    configuration: &Configuration,
+    cache: &ResponseCache,
+) -> Result<Stats, anyhow::Error> {
+    if configuration.offline() {
+        tracing::info!("Collecting statistics from local clones only");
+        return offline_stats(configuration).await;
+    }
+
+    let client = build_client(configuration)?;
+    let auth = Auth::new(
+        configuration.credentials()?,
+        client.clone(),
+        configuration.github_url(),
+    );
+
+    match configuration.provider() {
+        Provider::Github => {
+            let github = Github::new(configuration.clone(), client, auth).with_cache(cache.clone());
+            if configuration.preflight() {
+                preflight(&github).await?;
+            }
+            collect(&github).await
+        }
+        Provider::Gitlab => collect(&Gitlab::new(configuration.clone(), client, auth)).await,
+        Provider::Gitea => collect(&Gitea::new(configuration.clone(), client, auth)).await,
+    }
+}
+
+/// Collects the parts of `stats` made stale by `changes` again. Statistics merged
+/// from several sources are collected from scratch.
+async fn refresh_all(
+    configuration: &Configuration,
+    stats: &Stats,
+    changes: &[StatsChange],
+) -> Result<Stats, anyhow::Error> {
+    if !configuration.sources().is_empty() {
+        return collect_all(configuration).await;
+    }
+
+    let client = build_client(configuration)?;
+    let auth = Auth::new(
+        configuration.credentials()?,
+        client.clone(),
+        configuration.github_url(),
+    );
+
+    match configuration.provider() {
+        Provider::Github => {
+            Github::new(configuration.clone(), client, auth)
+                .refresh(stats, changes)
+                .await
+        }
+        Provider::Gitlab => {
+            Gitlab::new(configuration.clone(), client, auth)
+                .refresh(stats, changes)
+                .await
+        }
+        Provider::Gitea => {
+            Gitea::new(configuration.clone(), client, auth)
+                .refresh(stats, changes)
+                .await
+        }
+    }
+}
+
+async fn collect(forge: &impl Forge) -> Result<Stats, anyhow::Error> {
+    tracing::info!("Collecting statistics from {}", forge.provider());
+    forge.collect_stats().await
+}
+
+/// Renders every team member's cards into its own subfolder, then the leaderboard
+async fn run_team(configuration: &Configuration) -> Result<(), anyhow::Error> {
+    let badges = configuration.badges()?;
+    let cache = ResponseCache::default();
+    let mut team = vec![];
+
+    for login in configuration.team_members() {
+        let member_configuration = configuration.for_member(login);
+        let stats = collect_from(&member_configuration, &cache).await?;
+
+        let image_gen = ImageGen::new(
+            member_configuration.template_folder().to_string(),
+            member_configuration.output_folder().to_string(),
+        )
+        .with_languages_chart(configuration.languages_chart());
+        image_gen.generate_overview(&stats)?;
+        image_gen.generate_languages(&stats)?;
+        image_gen.generate_contributions_grid(&stats)?;
+        image_gen.generate_badges(&stats, &badges)?;
+        image_gen.generate_custom_cards(&stats, configuration.cards())?;
+        if configuration.dashboard() {
+            image_gen.generate_dashboard(&stats)?;
+        }
+        if configuration.csv_export() {
+            CsvExporter::new(member_configuration.output_folder().to_string()).export(&stats)?;
+        }
+
+        tracing::info!(
+            "{}: {} contributions, {} reviews",
+            login,
+            stats.total_contributions().unwrap_or_default(),
+            stats.reviews().unwrap_or_default()
+        );
+        team.push((login.to_string(), stats));
+    }
+
+    let image_gen = ImageGen::new(
+        configuration.template_folder().to_string(),
+        configuration.output_folder().to_string(),
+    );
+    image_gen.generate_leaderboard(&team)?;
+
+    if let Some(path) = configuration.metrics_textfile() {
+        let accounts = team
+            .iter()
+            .map(|(login, stats)| (login.as_str(), stats))
+            .collect::<Vec<_>>();
+        write_metrics_textfile(path, &accounts)?;
+        tracing::info!(
+            "Wrote the metrics of {} members to {}",
+            accounts.len(),
+            path
+        );
+    }
+    Ok(())
+}
+
+/// Prints which metrics the token can collect, so missing ones don't come as a surprise
+async fn preflight(github: &Github) -> Result<(), anyhow::Error> {
+    let report = github.preflight().await?;
+    eprintln!("{report}");
+
+    if let Some(viewer_login) = report
+        .viewer_login()
+        .filter(|_| !report.actor_matches_viewer())
+    {
+        tracing::warn!(
+            "GITHUB_ACTOR is {} but the token belongs to {}",
+            report.actor(),
+            viewer_login
+        );
+    }
+    for metric in report.unavailable() {
+        tracing::warn!(
+            "{} will be unavailable: {}",
+            metric.metric(),
+            metric.reason().unwrap_or_default()
+        );
+    }
+    tracing::info!(
+        "Rate limit: {}/{} remaining, resets at {}",
+        report.rate_limit().remaining(),
+        report.rate_limit().limit(),
+        report.rate_limit().reset_at()
+    );
 
-    tracing::info!("Total contributions: {}", total_contributions);
-    tracing::info!("Lines changed: {}, {}", lines_changed.0, lines_changed.1);
     Ok(())
 }
+
+fn build_client(configuration: &Configuration) -> Result<Client, anyhow::Error> {
+    let mut builder = Client::builder();
+    if let Some(path) = configuration.ca_certificate() {
+        let pem = std::fs::read(path)?;
+        for certificate in Certificate::from_pem_bundle(&pem)? {
+            builder = builder.add_root_certificate(certificate);
+        }
+    }
+
+    let client = builder
+        .user_agent("graphql-rust")
+        .timeout(Duration::from_secs(30))
+        .connect_timeout(Duration::from_secs(10))
+        .build()?;
+
+    Ok(client)
+}
@@ -0,0 +1,254 @@
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, TimeDelta, Utc};
+use jsonwebtoken::{Algorithm, EncodingKey, Header};
+use reqwest::Client;
+use secrecy::{ExposeSecret, SecretString};
+use tokio::sync::Mutex;
+
+use crate::domain::{AppClaims, InstallationToken, StatsError};
+
+/// Installation tokens are renewed this long before they expire
+const REFRESH_MARGIN: TimeDelta = TimeDelta::minutes(5);
+
+#[derive(Debug, Clone)]
+pub enum Credentials {
+    /// A personal access token
+    Token(SecretString),
+    /// GitHub App credentials, exchanged for installation tokens
+    App {
+        app_id: String,
+        installation_id: String,
+        private_key: SecretString,
+    },
+}
+
+/// Hands out the bearer token for API requests, renewing GitHub App
+/// installation tokens before they expire.
+#[derive(Debug, Clone)]
+pub struct Auth {
+    credentials: Credentials,
+    client: Client,
+    api_url: String,
+    installation_token: Arc<Mutex<Option<InstallationToken>>>,
+}
+
+impl Auth {
+    /// `api_url` is the REST API base the installation token is requested from
+    pub fn new(credentials: Credentials, client: Client, api_url: String) -> Self {
+        Self {
+            credentials,
+            client,
+            api_url,
+            installation_token: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    pub fn credentials(&self) -> &Credentials {
+        &self.credentials
+    }
+
+    pub async fn token(&self) -> Result<SecretString, StatsError> {
+        match &self.credentials {
+            Credentials::Token(token) => Ok(token.clone()),
+            Credentials::App {
+                app_id,
+                installation_id,
+                private_key,
+            } => {
+                let mut cached = self.installation_token.lock().await;
+                let now = Utc::now();
+                if let Some(token) = cached
+                    .as_ref()
+                    .filter(|token| !token.expires_within(REFRESH_MARGIN, now))
+                {
+                    return Ok(token.token().clone());
+                }
+
+                let jwt = app_jwt(app_id, private_key, now).map_err(|e| StatsError::Auth {
+                    message: "could not sign the GitHub App JWT".to_string(),
+                    source: Some(e.into()),
+                })?;
+                let token =
+                    self.exchange(&jwt, installation_id)
+                        .await
+                        .map_err(|e| StatsError::Auth {
+                            message: "could not obtain an installation token".to_string(),
+                            source: Some(e.into()),
+                        })?;
+                tracing::info!(
+                    "Obtained installation token, expires at {}",
+                    token.expires_at()
+                );
+                let secret = token.token().clone();
+                *cached = Some(token);
+                Ok(secret)
+            }
+        }
+    }
+
+    /// Exchanges the App JWT for an installation access token
+    async fn exchange(&self, jwt: &str, installation_id: &str) -> Result<InstallationToken> {
+        let response = self
+            .client
+            .post(format!(
+                "{}/app/installations/{}/access_tokens",
+                self.api_url, installation_id
+            ))
+            .bearer_auth(jwt)
+            .header(reqwest::header::ACCEPT, "application/vnd.github+json")
+            .send()
+            .await?
+            .error_for_status()
+            .context("Failed to exchange the App JWT for an installation token")?;
+
+        Ok(response.json::<InstallationToken>().await?)
+    }
+}
+
+/// Signs the RS256 JWT a GitHub App authenticates with
+pub fn app_jwt(app_id: &str, private_key: &SecretString, now: DateTime<Utc>) -> Result<String> {
+    let key = EncodingKey::from_rsa_pem(private_key.expose_secret().as_bytes())
+        .context("GitHub App private key is not a valid RSA PEM key")?;
+
+    Ok(jsonwebtoken::encode(
+        &Header::new(Algorithm::RS256),
+        &AppClaims::new(app_id, now),
+        &key,
+    )?)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use axum::{extract::Path, http::HeaderMap, http::StatusCode, routing::post, Json, Router};
+    use jsonwebtoken::{DecodingKey, Validation};
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::service::test_support::stand_in;
+
+    const PRIVATE_KEY: &str = include_str!("../../resources/test/github_app_test_key.pem");
+    const PUBLIC_KEY: &str = include_str!("../../resources/test/github_app_test_key.pub.pem");
+
+    fn decode(jwt: &str) -> jsonwebtoken::errors::Result<AppClaims> {
+        let mut validation = Validation::new(Algorithm::RS256);
+        // the claims are checked by the tests themselves
+        validation.validate_exp = false;
+        validation.required_spec_claims.clear();
+        jsonwebtoken::decode::<AppClaims>(
+            jwt,
+            &DecodingKey::from_rsa_pem(PUBLIC_KEY.as_bytes()).unwrap(),
+            &validation,
+        )
+        .map(|data| data.claims)
+    }
+
+    fn app_credentials() -> Credentials {
+        Credentials::App {
+            app_id: "1234".to_string(),
+            installation_id: "42".to_string(),
+            private_key: SecretString::from(PRIVATE_KEY),
+        }
+    }
+
+    /// A stand-in for the installation token endpoint: checks the JWT and hands out
+    /// numbered tokens expiring `expires_in` after the request
+    async fn token_server(expires_in: TimeDelta) -> (String, Arc<AtomicUsize>) {
+        let exchanges = Arc::new(AtomicUsize::new(0));
+        let counter = exchanges.clone();
+        let app = Router::new().route(
+            "/app/installations/{installation_id}/access_tokens",
+            post(
+                move |Path(installation_id): Path<String>, headers: HeaderMap| async move {
+                    let jwt = headers
+                        .get("authorization")
+                        .and_then(|value| value.to_str().ok())
+                        .and_then(|value| value.strip_prefix("Bearer "))
+                        .unwrap_or_default();
+                    match decode(jwt) {
+                        Ok(claims) if claims.iss() == "1234" && installation_id == "42" => {
+                            let n = counter.fetch_add(1, Ordering::SeqCst) + 1;
+                            Ok(Json(json!({
+                                "token": format!("ghs_{n}"),
+                                "expires_at": (Utc::now() + expires_in).to_rfc3339(),
+                            })))
+                        }
+                        _ => Err((StatusCode::UNAUTHORIZED, Json(Value::Null))),
+                    }
+                },
+            ),
+        );
+        let url = stand_in(app).await;
+
+        (url, exchanges)
+    }
+
+    #[test]
+    fn app_jwt_is_signed_with_backdated_short_lived_claims() {
+        let now = Utc::now();
+        let jwt = app_jwt("1234", &SecretString::from(PRIVATE_KEY), now).unwrap();
+
+        let claims = decode(&jwt).unwrap();
+        assert_eq!(claims.iss(), "1234");
+        assert_eq!(claims.iat(), now.timestamp() - 60);
+        assert_eq!(claims.exp(), now.timestamp() + 9 * 60);
+        assert!(claims.exp() - claims.iat() <= 10 * 60);
+    }
+
+    #[test]
+    fn app_jwt_rejects_a_key_that_is_not_rsa_pem() {
+        assert!(app_jwt("1234", &SecretString::from("not a key"), Utc::now()).is_err());
+    }
+
+    #[tokio::test]
+    async fn installation_token_is_reused_until_close_to_expiry() {
+        let (url, exchanges) = token_server(TimeDelta::hours(1)).await;
+        let auth = Auth::new(app_credentials(), Client::new(), url);
+
+        assert_eq!(auth.token().await.unwrap().expose_secret(), "ghs_1");
+        assert_eq!(auth.token().await.unwrap().expose_secret(), "ghs_1");
+        assert_eq!(exchanges.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn installation_token_is_renewed_within_the_refresh_margin() {
+        let (url, exchanges) = token_server(TimeDelta::minutes(4)).await;
+        let auth = Auth::new(app_credentials(), Client::new(), url);
+
+        assert_eq!(auth.token().await.unwrap().expose_secret(), "ghs_1");
+        assert_eq!(auth.token().await.unwrap().expose_secret(), "ghs_2");
+        assert_eq!(exchanges.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn rejected_exchange_is_an_auth_error() {
+        let (url, _) = token_server(TimeDelta::hours(1)).await;
+        let credentials = Credentials::App {
+            app_id: "9999".to_string(),
+            installation_id: "42".to_string(),
+            private_key: SecretString::from(PRIVATE_KEY),
+        };
+        let auth = Auth::new(credentials, Client::new(), url);
+
+        let error = auth.token().await.unwrap_err();
+
+        assert!(matches!(error, StatsError::Auth { .. }));
+        // the rejected request is kept as the cause
+        let source = std::error::Error::source(&error).unwrap();
+        assert!(source.to_string().contains("installation token"));
+    }
+
+    #[tokio::test]
+    async fn personal_token_is_handed_out_as_is() {
+        let auth = Auth::new(
+            Credentials::Token(SecretString::from("ghp_token")),
+            Client::new(),
+            "http://127.0.0.1:9".to_string(),
+        );
+
+        assert_eq!(auth.token().await.unwrap().expose_secret(), "ghp_token");
+    }
+}
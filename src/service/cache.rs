@@ -0,0 +1,42 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use crate::domain::{ContributorActivity, DailyViews};
+
+/// Per-repository REST responses shared between several `Github` instances,
+/// so repositories common to multiple team members are only fetched once.
+#[derive(Debug, Clone, Default)]
+pub struct ResponseCache {
+    contributors: Arc<Mutex<HashMap<String, Vec<ContributorActivity>>>>,
+    views: Arc<Mutex<HashMap<String, Vec<DailyViews>>>>,
+}
+
+impl ResponseCache {
+    pub fn contributors(&self, repo: &str) -> Option<Vec<ContributorActivity>> {
+        self.contributors
+            .lock()
+            .ok()
+            .and_then(|cache| cache.get(repo).cloned())
+    }
+
+    pub fn insert_contributors(&self, repo: &str, activity: Vec<ContributorActivity>) {
+        if let Ok(mut cache) = self.contributors.lock() {
+            cache.insert(repo.to_string(), activity);
+        }
+    }
+
+    pub fn views(&self, repo: &str) -> Option<Vec<DailyViews>> {
+        self.views
+            .lock()
+            .ok()
+            .and_then(|cache| cache.get(repo).cloned())
+    }
+
+    pub fn insert_views(&self, repo: &str, views: Vec<DailyViews>) {
+        if let Ok(mut cache) = self.views.lock() {
+            cache.insert(repo.to_string(), views);
+        }
+    }
+}
@@ -2,41 +2,282 @@ mod telemetry;
 
 pub use telemetry::*;
 
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::{TimeDelta, Utc};
 use confique::Config;
 use dotenvy::dotenv;
 use secrecy::SecretString;
+use serde::Deserialize;
+
+use crate::domain::{validate_cards, CustomCard, RepoFilter, StatsMetric};
+
+use super::Credentials;
 
 #[derive(Debug, Config, Clone)]
 pub struct Configuration {
+    /// Personal access token, not needed when authenticating as a GitHub App
     #[config(env = "ACCESS_TOKEN")]
-    access_token: SecretString,
+    access_token: Option<SecretString>,
+    #[config(env = "GITHUB_APP_ID")]
+    github_app_id: Option<String>,
+    #[config(env = "GITHUB_APP_INSTALLATION_ID")]
+    github_app_installation_id: Option<String>,
+    /// PEM encoded private key of the GitHub App
+    #[config(env = "GITHUB_APP_PRIVATE_KEY")]
+    github_app_private_key: Option<SecretString>,
     #[config(env = "GITHUB_ACTOR")]
     github_actor: String,
+    /// Comma separated logins; each member gets its own cards and a spot on the leaderboard.
+    /// A member's token is read from `ACCESS_TOKEN_<LOGIN>`, falling back to `ACCESS_TOKEN`.
+    #[config(env = "TEAM_MEMBERS", default = "")]
+    team_members: String,
+    /// When set, statistics describe this organization instead of the viewer
+    #[config(env = "ORGANIZATION")]
+    organization: Option<String>,
+    /// Alternate logins whose commits are attributed to `github_actor` as well
+    #[config(env = "GITHUB_ACTOR_ALIASES", default = "")]
+    github_actor_aliases: String,
     #[config(env = "EXCLUDED", default = "")]
     excluded_repos: String,
     #[config(env = "EXCLUDED_LANGS", default = "")]
     excluded_langs: String,
     #[config(env = "EXCLUDE_FORKED_REPOS", default = true)]
     exclude_forked_repos: bool,
+    #[config(env = "INCLUDE_CONTRIBUTED_REPOS", default = false)]
+    include_contributed_repos: bool,
+    #[config(env = "EXCLUDE_ARCHIVED_REPOS", default = false)]
+    exclude_archived_repos: bool,
+    #[config(env = "EXCLUDE_PRIVATE_REPOS", default = false)]
+    exclude_private_repos: bool,
+    #[config(env = "EXCLUDE_ORG_REPOS", default = false)]
+    exclude_org_repos: bool,
+    #[config(env = "MIN_STARS", default = 0)]
+    min_stars: i64,
+    /// Only repositories pushed to within this many days are counted
+    #[config(env = "PUSHED_WITHIN_DAYS")]
+    pushed_within_days: Option<i64>,
+    #[config(env = "LINES_CHANGED_BACKEND", default = "api")]
+    lines_changed_backend: LinesChangedBackend,
+    /// Comma separated paths of local clones analyzed by the `git` backend
+    #[config(env = "LOCAL_REPOS", default = "")]
+    local_repos: String,
+    /// Directory holding clones laid out as `<owner>/<name>`
+    #[config(env = "CLONE_CACHE_DIR")]
+    clone_cache_dir: Option<String>,
+    /// Comma separated commit author emails that belong to the user
+    #[config(env = "AUTHOR_EMAILS", default = "")]
+    author_emails: String,
+    /// Read every clone of `LOCAL_REPOS` and `CLONE_CACHE_DIR` without contacting the forge
+    #[config(env = "OFFLINE", default = false)]
+    offline: bool,
     #[config(env = "LANGUAGES_LIMIT", default = 10)]
     languages_limit: usize,
+    /// How the languages card draws the breakdown
+    #[config(env = "LANGUAGES_CHART", default = "bar")]
+    languages_chart: LanguagesChart,
+    /// Check the token's identity, scopes and rate limit before crawling
+    #[config(env = "PREFLIGHT", default = true)]
+    preflight: bool,
     #[config(env = "TEMPLATE_FOLDER", default = "resources/templates")]
     template_folder: String,
     #[config(env = "OUTPUT_FOLDER", default = "resources/generated")]
     output_folder: String,
-    #[config(default = "https://api.github.com")]
+    /// `generate` writes the cards once, `serve` renders them on request
+    #[config(env = "MODE", default = "generate")]
+    mode: Mode,
+    #[config(env = "SERVE_ADDRESS", default = "0.0.0.0:8080")]
+    serve_address: String,
+    /// How long collected statistics are served before they are collected again
+    #[config(env = "CACHE_TTL_SECONDS", default = 3600)]
+    cache_ttl_seconds: u64,
+    /// Secret GitHub signs webhook deliveries with, the webhook endpoint is disabled without it
+    #[config(env = "WEBHOOK_SECRET")]
+    webhook_secret: Option<SecretString>,
+    /// Quiet period after a webhook event before the statistics are refreshed
+    #[config(env = "WEBHOOK_DEBOUNCE_SECONDS", default = 30)]
+    webhook_debounce_seconds: u64,
+    /// Comma-separated metrics to write a badge for, e.g. `stars,contributions,top_language`
+    #[config(env = "BADGES", default = "")]
+    badges: String,
+    /// Also write a self-contained HTML report as `index.html` next to the cards
+    #[config(env = "DASHBOARD", default = false)]
+    dashboard: bool,
+    /// Also write the calendar, languages, repositories and traffic as CSV files next to the cards
+    #[config(env = "CSV_EXPORT", default = false)]
+    csv_export: bool,
+    /// File the statistics are written to in OpenMetrics format, for the node_exporter textfile collector
+    #[config(env = "METRICS_TEXTFILE")]
+    metrics_textfile: Option<String>,
+    /// Markdown file whose `github-stats` marker section is kept in sync with the cards
+    #[config(env = "README_PATH")]
+    readme_path: Option<String>,
+    /// Where the README loads the cards from, the output folder when unset
+    #[config(env = "README_IMAGE_BASE_URL")]
+    readme_image_base_url: Option<String>,
+    /// Also write the main metrics as text below the cards
+    #[config(env = "README_TEXT_METRICS", default = false)]
+    readme_text_metrics: bool,
+    /// Whether cards are written when some metrics couldn't be collected
+    #[config(env = "PARTIAL_RESULTS", default = "write")]
+    partial_results: PartialResults,
+    #[config(env = "PROVIDER", default = "github")]
+    provider: Provider,
+    #[config(env = "GITLAB_URL", default = "https://gitlab.com")]
+    gitlab_url: String,
+    #[config(env = "GITEA_URL", default = "https://codeberg.org")]
+    gitea_url: String,
+    /// REST API base URL
+    #[config(env = "GITHUB_API_URL", default = "https://api.github.com")]
     github_url: String,
+    /// GraphQL endpoint, derived from `github_url` when unset
+    #[config(env = "GITHUB_GRAPHQL_URL")]
+    graphql_url: Option<String>,
+    /// Host name of a GitHub Enterprise Server instance, e.g. `github.example.com`.
+    /// Takes precedence over `github_url` and `graphql_url`.
+    #[config(env = "GHES_HOST")]
+    ghes_host: Option<String>,
+    /// Path to a PEM file with additional CA certificates to trust
+    #[config(env = "CA_CERTIFICATE")]
+    ca_certificate: Option<String>,
+    /// Accounts whose statistics are merged into one set of cards, only read from
+    /// the configuration file
+    #[config(default = [], validate = valid_sources)]
+    sources: Vec<Source>,
+    /// Cards rendered from user templates next to the built-in ones, only read from
+    /// the configuration file
+    #[config(default = [], validate = valid_cards)]
+    cards: Vec<CustomCard>,
 }
 
 impl Configuration {
-    pub fn access_token(&self) -> &SecretString {
-        &self.access_token
+    pub fn access_token(&self) -> Option<&SecretString> {
+        self.access_token.as_ref()
+    }
+
+    /// GitHub App credentials when fully configured, the personal access token otherwise
+    pub fn credentials(&self) -> Result<Credentials> {
+        match (
+            &self.github_app_id,
+            &self.github_app_installation_id,
+            &self.github_app_private_key,
+        ) {
+            (Some(app_id), Some(installation_id), Some(private_key)) => Ok(Credentials::App {
+                app_id: app_id.clone(),
+                installation_id: installation_id.clone(),
+                private_key: private_key.clone(),
+            }),
+            _ => self
+                .access_token
+                .clone()
+                .map(Credentials::Token)
+                .ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "Either ACCESS_TOKEN or GITHUB_APP_ID, GITHUB_APP_INSTALLATION_ID and GITHUB_APP_PRIVATE_KEY must be set"
+                    )
+                }),
+        }
     }
 
     pub fn github_actor(&self) -> &str {
         &self.github_actor
     }
 
+    pub fn team_members(&self) -> Vec<&str> {
+        self.team_members
+            .split(',')
+            .map(str::trim)
+            .filter(|login| !login.is_empty())
+            .collect()
+    }
+
+    /// The configuration used to collect a single team member's statistics,
+    /// writing its cards into a subfolder named after the login. The member's
+    /// statistics come from its own account only, never the team's sources.
+    pub fn for_member(&self, login: &str) -> Self {
+        let token_var = format!("ACCESS_TOKEN_{}", login.to_uppercase().replace('-', "_"));
+        let member_token = std::env::var(token_var).ok().map(SecretString::from);
+        // a member's own token wins over shared App credentials
+        let github_app_id = match member_token {
+            Some(_) => None,
+            None => self.github_app_id.clone(),
+        };
+
+        Self {
+            access_token: member_token.or_else(|| self.access_token.clone()),
+            github_app_id,
+            github_actor: login.to_string(),
+            github_actor_aliases: String::new(),
+            team_members: String::new(),
+            organization: None,
+            sources: vec![],
+            output_folder: format!("{}/{}", self.output_folder, login),
+            ..self.clone()
+        }
+    }
+
+    pub fn cards(&self) -> &[CustomCard] {
+        &self.cards
+    }
+
+    pub fn sources(&self) -> &[Source] {
+        &self.sources
+    }
+
+    /// The configuration used to collect a single source's statistics
+    pub fn for_source(&self, source: &Source) -> Result<Self> {
+        let token = std::env::var(&source.token_env)
+            .map(SecretString::from)
+            .map_err(|_| anyhow::anyhow!("{} must be set", source.token_env))?;
+        let mut configuration = Self {
+            access_token: Some(token),
+            github_app_id: None,
+            github_actor: source
+                .actor
+                .clone()
+                .unwrap_or_else(|| self.github_actor.clone()),
+            github_actor_aliases: String::new(),
+            team_members: String::new(),
+            provider: source.provider,
+            // an organization describes the top-level account, not every source
+            organization: None,
+            sources: vec![],
+            ..self.clone()
+        };
+
+        if let Some(url) = &source.url {
+            match source.provider {
+                Provider::Github => {
+                    configuration.github_url = url.clone();
+                    configuration.graphql_url = None;
+                    configuration.ghes_host = None;
+                }
+                Provider::Gitlab => configuration.gitlab_url = url.clone(),
+                Provider::Gitea => configuration.gitea_url = url.clone(),
+            }
+        }
+
+        Ok(configuration)
+    }
+
+    pub fn organization(&self) -> Option<&str> {
+        self.organization.as_deref()
+    }
+
+    /// The organization or user the statistics are collected for
+    pub fn account(&self) -> &str {
+        self.organization().unwrap_or(self.github_actor())
+    }
+
+    /// The configured actor followed by any of its aliases
+    pub fn actor_logins(&self) -> Vec<&str> {
+        std::iter::once(self.github_actor.as_str())
+            .chain(self.github_actor_aliases.split(',').map(str::trim))
+            .filter(|login| !login.is_empty())
+            .collect()
+    }
+
     pub fn excluded_repos(&self) -> Vec<&str> {
         self.excluded_repos.split(',').collect()
     }
@@ -49,6 +290,79 @@ impl Configuration {
         self.exclude_forked_repos
     }
 
+    pub fn include_contributed_repos(&self) -> bool {
+        self.include_contributed_repos
+    }
+
+    pub fn exclude_archived_repos(&self) -> bool {
+        self.exclude_archived_repos
+    }
+
+    pub fn exclude_private_repos(&self) -> bool {
+        self.exclude_private_repos
+    }
+
+    pub fn exclude_org_repos(&self) -> bool {
+        self.exclude_org_repos
+    }
+
+    pub fn min_stars(&self) -> i64 {
+        self.min_stars
+    }
+
+    pub fn pushed_within_days(&self) -> Option<i64> {
+        self.pushed_within_days
+    }
+
+    pub fn repo_filter(&self) -> RepoFilter {
+        let excluded = self
+            .excluded_repos()
+            .into_iter()
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(str::to_string)
+            .collect();
+
+        RepoFilter::new(excluded)
+            .exclude_forks(self.exclude_forked_repos)
+            .exclude_archived(self.exclude_archived_repos)
+            .exclude_private(self.exclude_private_repos)
+            .exclude_org_owned(self.exclude_org_repos)
+            .min_stars(self.min_stars)
+            .pushed_after(
+                self.pushed_within_days
+                    .map(|days| Utc::now() - TimeDelta::days(days)),
+            )
+    }
+
+    pub fn lines_changed_backend(&self) -> LinesChangedBackend {
+        self.lines_changed_backend
+    }
+
+    pub fn local_repos(&self) -> Vec<&str> {
+        self.local_repos
+            .split(',')
+            .map(str::trim)
+            .filter(|path| !path.is_empty())
+            .collect()
+    }
+
+    pub fn offline(&self) -> bool {
+        self.offline
+    }
+
+    pub fn clone_cache_dir(&self) -> Option<&str> {
+        self.clone_cache_dir.as_deref()
+    }
+
+    pub fn author_emails(&self) -> Vec<&str> {
+        self.author_emails
+            .split(',')
+            .map(str::trim)
+            .filter(|email| !email.is_empty())
+            .collect()
+    }
+
     pub fn load_or_die() -> Self {
         dotenv()
             .map(|_| tracing::warn!("Variables used are being loaded from .env file"))
@@ -62,6 +376,20 @@ impl Configuration {
             .expect("Failed to load configuration")
     }
 
+    /// A configuration from the given fields, e.g. `{"github_actor": "octocat"}`, and the defaults
+    #[cfg(test)]
+    pub fn from_json(fields: serde_json::Value) -> Self {
+        let layer = serde_json::from_value(fields).expect("Invalid test configuration");
+        Configuration::builder()
+            .preloaded(layer)
+            .load()
+            .expect("Failed to load configuration")
+    }
+
+    pub fn preflight(&self) -> bool {
+        self.preflight
+    }
+
     pub fn template_folder(&self) -> &str {
         &self.template_folder
     }
@@ -74,7 +402,328 @@ impl Configuration {
         self.languages_limit
     }
 
-    pub fn github_url(&self) -> &str {
-        &self.github_url
+    pub fn languages_chart(&self) -> LanguagesChart {
+        self.languages_chart
+    }
+
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    pub fn serve_address(&self) -> &str {
+        &self.serve_address
+    }
+
+    pub fn cache_ttl(&self) -> Duration {
+        Duration::from_secs(self.cache_ttl_seconds)
+    }
+
+    pub fn webhook_secret(&self) -> Option<&SecretString> {
+        self.webhook_secret.as_ref()
+    }
+
+    pub fn webhook_debounce(&self) -> Duration {
+        Duration::from_secs(self.webhook_debounce_seconds)
+    }
+
+    pub fn badges(&self) -> Result<Vec<StatsMetric>> {
+        self.badges
+            .split(',')
+            .map(str::trim)
+            .filter(|badge| !badge.is_empty())
+            .map(str::parse)
+            .collect()
+    }
+
+    pub fn dashboard(&self) -> bool {
+        self.dashboard
+    }
+
+    pub fn csv_export(&self) -> bool {
+        self.csv_export
+    }
+
+    pub fn metrics_textfile(&self) -> Option<&str> {
+        self.metrics_textfile.as_deref()
+    }
+
+    pub fn readme_path(&self) -> Option<&str> {
+        self.readme_path.as_deref()
+    }
+
+    pub fn readme_image_base_url(&self) -> &str {
+        self.readme_image_base_url
+            .as_deref()
+            .unwrap_or(&self.output_folder)
+    }
+
+    pub fn readme_text_metrics(&self) -> bool {
+        self.readme_text_metrics
+    }
+
+    pub fn allow_partial_results(&self) -> bool {
+        self.partial_results == PartialResults::Write
+    }
+
+    pub fn provider(&self) -> Provider {
+        self.provider
+    }
+
+    /// URL of the configured forge, telling apart repositories of different instances
+    pub fn forge_url(&self) -> String {
+        match self.provider {
+            Provider::Github => self.github_url(),
+            Provider::Gitlab => self.gitlab_url.trim_end_matches('/').to_string(),
+            Provider::Gitea => self.gitea_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    pub fn gitlab_url(&self) -> &str {
+        &self.gitlab_url
+    }
+
+    pub fn gitea_url(&self) -> &str {
+        &self.gitea_url
+    }
+
+    /// REST API base URL; GHES serves it under `/api/v3`
+    pub fn github_url(&self) -> String {
+        match &self.ghes_host {
+            Some(host) => format!("https://{}/api/v3", host.trim_end_matches('/')),
+            None => self.github_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// GraphQL endpoint; GHES serves it at `/api/graphql` rather than next to the REST API
+    pub fn graphql_url(&self) -> String {
+        match (&self.ghes_host, &self.graphql_url) {
+            (Some(host), _) => format!("https://{}/api/graphql", host.trim_end_matches('/')),
+            (None, Some(graphql_url)) => graphql_url.trim_end_matches('/').to_string(),
+            (None, None) => format!("{}/graphql", self.github_url.trim_end_matches('/')),
+        }
+    }
+
+    pub fn ca_certificate(&self) -> Option<&str> {
+        self.ca_certificate.as_deref()
+    }
+}
+
+/// The forge statistics are collected from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Provider {
+    Github,
+    Gitlab,
+    /// Gitea and its Forgejo fork share the same API
+    Gitea,
+}
+
+/// Whether the binary runs once or keeps serving the cards over HTTP
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Mode {
+    Generate,
+    Serve,
+}
+
+/// Chart of the languages card
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LanguagesChart {
+    /// A progress bar above a two-column list
+    #[default]
+    Bar,
+    /// A donut beside a one-column legend, as wide as the other cards
+    Donut,
+    /// A donut beside a two-column legend, fitting twice as many languages
+    DonutWide,
+}
+
+/// What happens when some metrics fail to be collected
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PartialResults {
+    /// Write the cards with the failed metrics shown as unavailable
+    Write,
+    /// Fail the run
+    Fail,
+}
+
+/// An account statistics are collected from when merging several of them
+#[derive(Debug, Clone, Deserialize)]
+pub struct Source {
+    provider: Provider,
+    /// REST API base URL for GitHub, instance URL otherwise; the provider's default when unset
+    url: Option<String>,
+    /// Login the statistics are collected for, `GITHUB_ACTOR` when unset. Only GitHub
+    /// sources take one, the others describe the token's user.
+    actor: Option<String>,
+    /// Environment variable holding the source's access token
+    token_env: String,
+}
+
+impl Source {
+    pub fn provider(&self) -> Provider {
+        self.provider
+    }
+
+    pub fn url(&self) -> Option<&str> {
+        self.url.as_deref()
+    }
+
+    pub fn actor(&self) -> Option<&str> {
+        self.actor.as_deref()
+    }
+}
+
+/// confique hands validators a reference to the field as declared
+#[allow(clippy::ptr_arg)]
+fn valid_cards(cards: &Vec<CustomCard>) -> Result<(), String> {
+    validate_cards(cards)
+}
+
+/// GitLab and Gitea collect the statistics of the token's user, so an `actor` set for
+/// them would be silently ignored
+#[allow(clippy::ptr_arg)]
+fn valid_sources(sources: &Vec<Source>) -> Result<(), String> {
+    match sources
+        .iter()
+        .find(|source| source.actor.is_some() && source.provider != Provider::Github)
+    {
+        Some(source) => Err(format!(
+            "source `{}` sets an actor, which only GitHub sources support",
+            source.token_env
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Where lines added and deleted are computed from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LinesChangedBackend {
+    /// The `stats/contributors` REST endpoint
+    Api,
+    /// Local git clones, see `LOCAL_REPOS` and `CLONE_CACHE_DIR`
+    Git,
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn cards_escaping_the_output_folder_are_rejected_at_load() {
+        let layer = serde_json::from_value::<<Configuration as Config>::Partial>(json!({
+            "github_actor": "jane",
+            "cards": [{ "template": "card.svg", "output": "../card.svg", "metrics": [] }],
+        }));
+
+        let Err(error) = layer else {
+            panic!("expected the card to be rejected");
+        };
+        assert!(error.to_string().contains("without directories"), "{error}");
+    }
+
+    #[test]
+    fn actors_of_sources_other_than_github_are_rejected_at_load() {
+        let layer = serde_json::from_value::<<Configuration as Config>::Partial>(json!({
+            "github_actor": "jane",
+            "sources": [
+                { "provider": "github", "actor": "jane-at-work", "token_env": "ACCESS_TOKEN_WORK" },
+                { "provider": "gitlab", "actor": "jane", "token_env": "GITLAB_TOKEN" },
+            ],
+        }));
+
+        let Err(error) = layer else {
+            panic!("expected the GitLab actor to be rejected");
+        };
+        assert!(error.to_string().contains("GITLAB_TOKEN"), "{error}");
+    }
+
+    #[test]
+    fn github_endpoints_default_to_github_com() {
+        let configuration = Configuration::from_json(json!({ "github_actor": "jane" }));
+
+        assert_eq!(configuration.github_url(), "https://api.github.com");
+        assert_eq!(
+            configuration.graphql_url(),
+            "https://api.github.com/graphql"
+        );
+    }
+
+    #[test]
+    fn ghes_host_derives_both_endpoints() {
+        for host in ["github.example.com", "github.example.com/"] {
+            let configuration = Configuration::from_json(json!({
+                "github_actor": "jane",
+                "ghes_host": host,
+                // the host takes precedence over explicit endpoints
+                "github_url": "https://api.github.com",
+                "graphql_url": "https://api.github.com/graphql",
+            }));
+
+            assert_eq!(
+                configuration.github_url(),
+                "https://github.example.com/api/v3",
+                "{host}"
+            );
+            assert_eq!(
+                configuration.graphql_url(),
+                "https://github.example.com/api/graphql",
+                "{host}"
+            );
+        }
+    }
+
+    #[test]
+    fn explicit_endpoints_are_used_without_trailing_slashes() {
+        let configuration = Configuration::from_json(json!({
+            "github_actor": "jane",
+            "github_url": "https://github.example.com/api/v3/",
+            "graphql_url": "https://github.example.com/api/graphql/",
+        }));
+
+        assert_eq!(
+            configuration.github_url(),
+            "https://github.example.com/api/v3"
+        );
+        assert_eq!(
+            configuration.graphql_url(),
+            "https://github.example.com/api/graphql"
+        );
+    }
+
+    #[test]
+    fn graphql_endpoint_is_derived_from_the_rest_url() {
+        let configuration = Configuration::from_json(json!({
+            "github_actor": "jane",
+            "github_url": "https://proxy.example.com/github/",
+        }));
+
+        assert_eq!(
+            configuration.graphql_url(),
+            "https://proxy.example.com/github/graphql"
+        );
+    }
+
+    #[test]
+    fn members_are_collected_from_their_own_account_only() {
+        let configuration = Configuration::from_json(json!({
+            "github_actor": "acme-team",
+            "organization": "acme",
+            "team_members": "jane,john",
+            "sources": [{ "provider": "gitlab", "token_env": "GITLAB_TOKEN" }],
+        }));
+
+        let member = configuration.for_member("jane");
+
+        assert_eq!(member.account(), "jane");
+        assert_eq!(member.actor_logins(), vec!["jane"]);
+        assert!(member.organization().is_none());
+        assert!(member.sources().is_empty());
+        assert!(member.team_members().is_empty());
     }
 }
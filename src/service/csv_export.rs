@@ -0,0 +1,186 @@
+use std::fs;
+
+use anyhow::{Context, Result};
+
+use crate::domain::Stats;
+
+/// Writes the tables behind the cards as CSV files, one header row followed by
+/// one row per record. Columns never move; new ones are only ever appended.
+/// Metrics that couldn't be collected leave their cells, or their whole table, empty.
+#[derive(Debug, Clone)]
+pub struct CsvExporter {
+    output_folder: String,
+}
+
+impl CsvExporter {
+    pub fn new(output_folder: String) -> Self {
+        Self { output_folder }
+    }
+
+    #[tracing::instrument(skip(stats))]
+    pub fn export(&self, stats: &Stats) -> Result<()> {
+        fs::create_dir_all(&self.output_folder)
+            .with_context(|| format!("Failed to create {}", self.output_folder))?;
+
+        for (name, table) in [
+            ("contribution_calendar.csv", contribution_calendar(stats)),
+            ("languages.csv", languages(stats)),
+            ("repositories.csv", repositories(stats)),
+            ("traffic.csv", traffic(stats)),
+        ] {
+            let path = format!("{}/{}", self.output_folder, name);
+            fs::write(&path, table).with_context(|| format!("Failed to write {path}"))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// One day per row, oldest first
+fn contribution_calendar(stats: &Stats) -> String {
+    let rows = stats
+        .contribution_calendar()
+        .unwrap_or_default()
+        .iter()
+        .flat_map(|week| week.days())
+        .map(|day| {
+            vec![
+                day.date().to_string(),
+                day.count().to_string(),
+                day.level().to_string(),
+            ]
+        });
+
+    table(&["date", "count", "level"], rows)
+}
+
+/// One language per row, largest first
+fn languages(stats: &Stats) -> String {
+    let rows = stats.all_languages().iter().map(|(name, language)| {
+        vec![
+            name.clone(),
+            optional(language.bytes()),
+            language.occurrences().to_string(),
+            format!("{:.4}", language.proportion()),
+        ]
+    });
+
+    table(&["language", "bytes", "repositories", "percent"], rows)
+}
+
+/// One repository per row, by name
+fn repositories(stats: &Stats) -> String {
+    let lines_changed = stats.lines_changed_by_repo();
+    let mut repos = stats.repos().iter().collect::<Vec<_>>();
+    repos.sort_by_key(|repo| repo.name_with_owner().to_lowercase());
+
+    let rows = repos.into_iter().map(|repo| {
+        let changed = lines_changed.map(|by_repo| {
+            by_repo
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(repo.name_with_owner()))
+                .map(|(_, changed)| *changed)
+                .unwrap_or_default()
+        });
+        let language = repo
+            .languages()
+            .iter()
+            .max_by_key(|language| language.size())
+            .map(|language| language.name().to_string());
+
+        vec![
+            repo.name_with_owner().to_string(),
+            repo.stargazers().to_string(),
+            repo.forks().to_string(),
+            optional(changed.map(|(added, _)| added)),
+            optional(changed.map(|(_, deleted)| deleted)),
+            language.unwrap_or_default(),
+            repo.is_fork().to_string(),
+            repo.is_archived().to_string(),
+            repo.is_private().to_string(),
+            optional(repo.pushed_at().map(|pushed_at| pushed_at.to_rfc3339())),
+        ]
+    });
+
+    table(
+        &[
+            "repository",
+            "stars",
+            "forks",
+            "lines_added",
+            "lines_deleted",
+            "language",
+            "fork",
+            "archived",
+            "private",
+            "pushed_at",
+        ],
+        rows,
+    )
+}
+
+/// One repository and day per row, by day then repository
+fn traffic(stats: &Stats) -> String {
+    let mut traffic = stats
+        .traffic()
+        .unwrap_or_default()
+        .iter()
+        .collect::<Vec<_>>();
+    traffic.sort_by(|a, b| {
+        (a.date(), a.repository().to_lowercase()).cmp(&(b.date(), b.repository().to_lowercase()))
+    });
+
+    let rows = traffic.into_iter().map(|day| {
+        vec![
+            day.date().to_string(),
+            day.repository().to_string(),
+            day.count().to_string(),
+            day.uniques().to_string(),
+        ]
+    });
+
+    table(&["date", "repository", "views", "unique_visitors"], rows)
+}
+
+fn table(header: &[&str], rows: impl Iterator<Item = Vec<String>>) -> String {
+    let mut table = header.join(",");
+    table.push('\n');
+    for row in rows {
+        let row = row.iter().map(|cell| field(cell)).collect::<Vec<_>>();
+        table.push_str(&row.join(","));
+        table.push('\n');
+    }
+    table
+}
+
+fn optional(value: Option<impl ToString>) -> String {
+    value.map(|value| value.to_string()).unwrap_or_default()
+}
+
+/// Quotes a field containing a separator, quote or line break, as RFC 4180 describes
+fn field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service::test_support::stats;
+
+    #[test]
+    fn languages_table_holds_languages_beyond_the_cards_limit() {
+        let stats = stats();
+
+        assert_eq!(stats.languages().len(), 1);
+        assert_eq!(
+            languages(&stats),
+            "language,bytes,repositories,percent\n\
+             Rust,300,1,75.0000\n\
+             Shell,100,1,25.0000\n"
+        );
+    }
+}
@@ -0,0 +1,461 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+
+use crate::domain::{calendar_from_counts, Metric, RepoHistory, Stats, StatsBuilder};
+
+use super::Configuration;
+
+/// Reads the user's additions, deletions and commit times straight from local
+/// git clones, without touching the GitHub API.
+#[derive(Debug, Clone)]
+pub struct GitHistory {
+    /// Every author's commits count when `None`
+    author_emails: Option<Vec<String>>,
+}
+
+impl GitHistory {
+    pub fn new(author_emails: Vec<String>) -> Self {
+        Self {
+            author_emails: Some(author_emails),
+        }
+    }
+
+    /// Counts the commits of the configured `AUTHOR_EMAILS`
+    pub fn for_user(configuration: &Configuration) -> Self {
+        let author_emails = configuration
+            .author_emails()
+            .into_iter()
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+        if author_emails.is_empty() {
+            tracing::warn!("AUTHOR_EMAILS is empty, no commits will be attributed to the user");
+        }
+        Self::new(author_emails)
+    }
+
+    /// Counts the commits of everyone, as organization statistics do
+    pub fn every_author() -> Self {
+        Self {
+            author_emails: None,
+        }
+    }
+
+    /// Walks every commit reachable from `HEAD` and sums the line changes of the
+    /// non-merge commits authored by one of the configured emails, or by anyone.
+    #[tracing::instrument(skip(self))]
+    pub fn analyze(&self, name: &str, path: &Path) -> Result<RepoHistory> {
+        let mut repo = gix::open(path)
+            .with_context(|| format!("Failed to open git repository at {}", path.display()))?;
+        repo.object_cache_size_if_unset(
+            repo.compute_object_cache_size_for_tree_diffs(&*repo.index_or_empty()?),
+        );
+
+        let head = repo.head_id().context("Repository has no HEAD commit")?;
+        let (mut added, mut deleted) = (0, 0);
+        let mut commits = vec![];
+        let mut authors = BTreeSet::new();
+
+        for info in repo.rev_walk([head]).all()? {
+            let commit = info?.object()?;
+            let author = commit.author()?;
+            let email = author.trim().email.to_string();
+            if self.author_emails.as_ref().is_some_and(|author_emails| {
+                !author_emails
+                    .iter()
+                    .any(|known| known.eq_ignore_ascii_case(&email))
+            }) {
+                continue;
+            }
+
+            let parents = commit.parent_ids().collect::<Vec<_>>();
+            // merge commits only repeat changes that are already counted on their branches
+            if parents.len() > 1 {
+                continue;
+            }
+
+            let parent_tree = match parents.first() {
+                Some(parent) => parent.object()?.into_commit().tree()?,
+                None => repo.empty_tree(),
+            };
+            let stats = parent_tree
+                .changes()?
+                .options(|options| {
+                    options.track_rewrites(None);
+                })
+                .stats(&commit.tree()?)?;
+
+            added += stats.lines_added as i64;
+            deleted += stats.lines_removed as i64;
+            if let Some(time) = DateTime::<Utc>::from_timestamp(author.seconds(), 0) {
+                commits.push(time);
+            }
+            authors.insert(email.to_lowercase());
+        }
+
+        tracing::debug!(
+            "{}: {} commits, {} lines added, {} deleted",
+            name,
+            commits.len(),
+            added,
+            deleted
+        );
+
+        Ok(RepoHistory::new(
+            name.to_string(),
+            added,
+            deleted,
+            commits,
+            authors.into_iter().collect(),
+        ))
+    }
+}
+
+/// The clones the `git` backend analyzes, named `owner/name` like the forge names them:
+/// each path of `LOCAL_REPOS` after its default remote, and every `<owner>/<name>`
+/// directory of `CLONE_CACHE_DIR`
+pub fn discover_clones(
+    local_repos: &[&str],
+    clone_cache_dir: Option<&str>,
+) -> Vec<(String, PathBuf)> {
+    let mut clones = local_repos
+        .iter()
+        .map(PathBuf::from)
+        .map(|path| (clone_name(&path), path))
+        .collect::<Vec<_>>();
+
+    if let Some(cache_dir) = clone_cache_dir {
+        let entries = |dir: &Path| {
+            fs::read_dir(dir)
+                .into_iter()
+                .flatten()
+                .flatten()
+                .filter(|entry| entry.path().is_dir())
+                .collect::<Vec<_>>()
+        };
+        for owner in entries(Path::new(cache_dir)) {
+            for repo in entries(&owner.path()) {
+                let name = format!(
+                    "{}/{}",
+                    owner.file_name().to_string_lossy(),
+                    repo.file_name().to_string_lossy()
+                );
+                clones.push((name, repo.path()));
+            }
+        }
+    }
+
+    clones
+}
+
+/// `owner/name` of a clone's default remote, its directory name when it has none
+fn clone_name(path: &Path) -> String {
+    let remote = gix::open(path).ok().and_then(|repo| {
+        repo.find_default_remote(gix::remote::Direction::Fetch)?
+            .ok()?
+            .url(gix::remote::Direction::Fetch)
+            .and_then(|url| repo_name_from_url(&url.to_bstring().to_string()))
+    });
+
+    remote.unwrap_or_else(|| {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.display().to_string());
+        tracing::warn!(
+            "{} has no remote, it can only be matched by its directory name {}",
+            path.display(),
+            name
+        );
+        name
+    })
+}
+
+/// `owner/name` out of an HTTPS, SSH or scp-like remote URL
+fn repo_name_from_url(url: &str) -> Option<String> {
+    let path = url.trim_end_matches('/');
+    let path = path.strip_suffix(".git").unwrap_or(path);
+    let mut segments = path.rsplit(['/', ':']);
+    let name = segments.next().filter(|name| !name.is_empty())?;
+    let owner = segments.next().filter(|owner| !owner.is_empty())?;
+    Some(format!("{owner}/{name}"))
+}
+
+/// Keeps the clones of `repos`, under the name the forge uses. A clone without a remote
+/// matches the one repository sharing its directory name.
+fn match_clones(clones: Vec<(String, PathBuf)>, repos: &[String]) -> Vec<(String, PathBuf)> {
+    clones
+        .into_iter()
+        .filter_map(|(name, path)| {
+            let matched = repos
+                .iter()
+                .find(|repo| repo.eq_ignore_ascii_case(&name))
+                .or_else(|| {
+                    if name.contains('/') {
+                        return None;
+                    }
+                    let mut same_name = repos.iter().filter(|repo| {
+                        repo.rsplit('/')
+                            .next()
+                            .is_some_and(|repo_name| repo_name.eq_ignore_ascii_case(&name))
+                    });
+                    match (same_name.next(), same_name.next()) {
+                        (Some(repo), None) => Some(repo),
+                        _ => None,
+                    }
+                });
+            match matched {
+                Some(repo) => Some((repo.clone(), path)),
+                None => {
+                    tracing::info!(
+                        "{} ({}) isn't among the counted repositories, skipping it",
+                        name,
+                        path.display()
+                    );
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// Analyzes the discovered clones, only those of `repos` when the repository set is
+/// known. A repository cloned more than once is analyzed once.
+#[tracing::instrument(skip(configuration))]
+pub async fn analyze_clones(
+    configuration: &Configuration,
+    repos: Option<&[String]>,
+    git_history: GitHistory,
+) -> Result<Vec<RepoHistory>> {
+    let clones = discover_clones(
+        &configuration.local_repos(),
+        configuration.clone_cache_dir(),
+    );
+    let clones = match repos {
+        Some(repos) => match_clones(clones, repos),
+        None => clones,
+    };
+    let mut seen = HashSet::new();
+    let clones = clones
+        .into_iter()
+        .filter(|(name, path)| {
+            let first = seen.insert(name.to_lowercase());
+            if !first {
+                tracing::info!("{} is already analyzed, skipping {}", name, path.display());
+            }
+            first
+        })
+        .collect::<Vec<_>>();
+
+    // gix is blocking, keep it off the async workers
+    let history = tokio::task::spawn_blocking(move || {
+        clones
+            .iter()
+            .filter_map(|(name, path)| match git_history.analyze(name, path) {
+                Ok(history) => Some(history),
+                Err(e) => {
+                    tracing::error!("Failed to analyze {}: {:?}", path.display(), e);
+                    None
+                }
+            })
+            .collect::<Vec<_>>()
+    })
+    .await?;
+
+    Ok(history)
+}
+
+/// Statistics read from local clones alone, without any forge: lines changed, the user's
+/// commits as contributions and their calendar. What only the forge knows is unavailable,
+/// the repository counters stay at zero.
+pub async fn offline_stats(configuration: &Configuration) -> Result<Stats> {
+    let history = analyze_clones(configuration, None, GitHistory::for_user(configuration)).await?;
+
+    let mut counts = BTreeMap::new();
+    for commit in history.iter().flat_map(RepoHistory::commits) {
+        *counts.entry(commit.date_naive()).or_insert(0) += 1;
+    }
+    const OFFLINE: &str = "not collected in offline mode";
+
+    let stats = StatsBuilder::default()
+        .name(configuration.github_actor().to_string())
+        .stargazers(0)
+        .forks(0)
+        .languages(vec![])
+        .repos(vec![])
+        .total_contributions(counts.values().sum::<i64>())
+        .lines_changed_by_repo(
+            history
+                .iter()
+                .map(|repo| (repo.name().to_string(), (repo.added(), repo.deleted())))
+                .collect::<Vec<_>>(),
+        )
+        .views(Metric::Unavailable(OFFLINE.to_string()))
+        .reviews(Metric::Unavailable(OFFLINE.to_string()))
+        .contribution_calendar(calendar_from_counts(&counts, Utc::now().date_naive()))
+        .history(history)
+        .build()?;
+
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use gix::{
+        actor::Signature,
+        objs::{tree::Entry, tree::EntryKind, Commit, Tree},
+        ObjectId,
+    };
+
+    use super::*;
+
+    /// A repository where jane writes three lines and rewrites one, john adds a file
+    /// of two lines on a branch and jane merges it
+    fn repository(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "github-stats-history-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&path);
+        let repo = gix::init(&path).unwrap();
+
+        let commit = |email: &str, seconds: i64, files: &[(&str, &str)], parents: &[ObjectId]| {
+            let entries = files
+                .iter()
+                .map(|(filename, content)| Entry {
+                    mode: EntryKind::Blob.into(),
+                    filename: (*filename).into(),
+                    oid: repo.write_blob(content).unwrap().detach(),
+                })
+                .collect();
+            let signature = Signature {
+                name: "someone".into(),
+                email: email.into(),
+                time: gix::date::Time::new(seconds, 0),
+            };
+            repo.write_object(&Commit {
+                tree: repo.write_object(&Tree { entries }).unwrap().detach(),
+                parents: parents.iter().copied().collect(),
+                author: signature.clone(),
+                committer: signature,
+                encoding: None,
+                message: "change".into(),
+                extra_headers: vec![],
+            })
+            .unwrap()
+            .detach()
+        };
+
+        let first = commit(
+            "jane@example.com",
+            1_700_000_000,
+            &[("a.txt", "1\n2\n3\n")],
+            &[],
+        );
+        let rewrite = commit(
+            "jane@example.com",
+            1_700_086_400,
+            &[("a.txt", "1\ntwo\n3\n")],
+            &[first],
+        );
+        let foreign = commit(
+            "john@example.com",
+            1_700_172_800,
+            &[("a.txt", "1\n2\n3\n"), ("b.txt", "x\ny\n")],
+            &[first],
+        );
+        let merge = commit(
+            "jane@example.com",
+            1_700_259_200,
+            &[("a.txt", "1\ntwo\n3\n"), ("b.txt", "x\ny\n")],
+            &[rewrite, foreign],
+        );
+        // a loose reference, as the test repository has no committer to write a reflog for
+        let head = repo.head_name().unwrap().unwrap();
+        fs::write(
+            path.join(".git").join(head.as_bstr().to_string()),
+            format!("{merge}\n"),
+        )
+        .unwrap();
+
+        path
+    }
+
+    #[test]
+    fn only_the_authors_commits_are_counted_without_merges() {
+        let path = repository("author");
+
+        let history = GitHistory::new(vec!["Jane@Example.com".to_string()])
+            .analyze("octo/hello-world", &path)
+            .unwrap();
+
+        fs::remove_dir_all(&path).unwrap();
+        assert_eq!(history.name(), "octo/hello-world");
+        assert_eq!((history.added(), history.deleted()), (4, 1));
+        assert_eq!(history.commit_count(), 2);
+        assert_eq!(history.authors(), ["jane@example.com"]);
+    }
+
+    #[test]
+    fn every_authors_commits_are_counted_for_organizations() {
+        let path = repository("everyone");
+
+        let history = GitHistory::every_author()
+            .analyze("octo/hello-world", &path)
+            .unwrap();
+
+        fs::remove_dir_all(&path).unwrap();
+        assert_eq!((history.added(), history.deleted()), (6, 1));
+        assert_eq!(history.commit_count(), 3);
+        assert_eq!(history.authors(), ["jane@example.com", "john@example.com"]);
+    }
+
+    #[test]
+    fn repo_name_from_remote_urls() {
+        for url in [
+            "https://github.com/octo/hello-world.git",
+            "https://github.com/octo/hello-world/",
+            "git@github.com:octo/hello-world.git",
+            "ssh://git@gitlab.example.com:2222/octo/hello-world",
+        ] {
+            assert_eq!(
+                repo_name_from_url(url).as_deref(),
+                Some("octo/hello-world"),
+                "{url}"
+            );
+        }
+        assert_eq!(repo_name_from_url("hello-world"), None);
+    }
+
+    #[test]
+    fn clones_match_the_counted_repositories() {
+        let repos = vec!["Octo/Hello-World".to_string(), "octo/spoon".to_string()];
+        let clones = vec![
+            ("octo/hello-world".to_string(), PathBuf::from("/a")),
+            ("spoon".to_string(), PathBuf::from("/b")),
+            ("octo/excluded".to_string(), PathBuf::from("/c")),
+        ];
+
+        assert_eq!(
+            match_clones(clones, &repos),
+            vec![
+                ("Octo/Hello-World".to_string(), PathBuf::from("/a")),
+                ("octo/spoon".to_string(), PathBuf::from("/b")),
+            ]
+        );
+    }
+
+    #[test]
+    fn ambiguous_directory_names_match_nothing() {
+        let repos = vec!["octo/spoon".to_string(), "other/spoon".to_string()];
+        let clones = vec![("spoon".to_string(), PathBuf::from("/b"))];
+
+        assert!(match_clones(clones, &repos).is_empty());
+    }
+}
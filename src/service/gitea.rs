@@ -0,0 +1,469 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use anyhow::Result;
+use chrono::{NaiveDate, Utc};
+use reqwest::{header::AUTHORIZATION, Client};
+use secrecy::ExposeSecret;
+use serde::de::{DeserializeOwned, IgnoredAny};
+
+use crate::{
+    algebra::Forge,
+    domain::{
+        calendar_from_counts, top_languages, GiteaHeatmapEntry, GiteaRepo, GiteaUser, Language,
+        Metric, RepoSource, Repository, RepositorySet, Stats, StatsBuilder, StatsError,
+    },
+};
+
+use super::{check_status, Auth, Configuration};
+
+const PAGE_SIZE: usize = 50;
+
+/// Collects statistics from the Gitea / Forgejo REST API (v1)
+#[derive(Debug)]
+pub struct Gitea {
+    configuration: Configuration,
+    client: Client,
+    auth: Auth,
+}
+
+impl Gitea {
+    pub fn new(configuration: Configuration, client: Client, auth: Auth) -> Self {
+        Self {
+            configuration,
+            client,
+            auth,
+        }
+    }
+
+    pub fn api_url(&self) -> String {
+        format!(
+            "{}/api/v1",
+            self.configuration.gitea_url().trim_end_matches('/')
+        )
+    }
+
+    async fn get<T: DeserializeOwned>(&self, path: &str, query: &[(&str, String)]) -> Result<T> {
+        let token = self.auth.token().await?;
+        let response = self
+            .client
+            .get(format!("{}{}", self.api_url(), path))
+            .header(AUTHORIZATION, format!("token {}", token.expose_secret()))
+            .query(query)
+            .send()
+            .await?;
+        let response = check_status(response, &format!("Gitea {path}")).await?;
+
+        Ok(response.json::<T>().await.map_err(|e| malformed(path, e))?)
+    }
+
+    /// Requests pages until one comes back empty, or `x-total-count` items arrived.
+    /// Instances cap `limit` at their `MAX_RESPONSE_ITEMS`, so a short page isn't the last.
+    async fn get_paginated<T: DeserializeOwned>(&self, path: &str) -> Result<Vec<T>> {
+        let token = self.auth.token().await?;
+        let mut items = vec![];
+
+        for page in 1.. {
+            let response = self
+                .client
+                .get(format!("{}{}", self.api_url(), path))
+                .header(AUTHORIZATION, format!("token {}", token.expose_secret()))
+                .query(&[("page", page.to_string()), ("limit", PAGE_SIZE.to_string())])
+                .send()
+                .await?;
+            let response = check_status(response, &format!("Gitea {path}")).await?;
+
+            let total = response
+                .headers()
+                .get("x-total-count")
+                .and_then(|total| total.to_str().ok())
+                .and_then(|total| total.parse::<usize>().ok());
+            let batch = response
+                .json::<Vec<T>>()
+                .await
+                .map_err(|e| malformed(path, e))?;
+            if batch.is_empty() {
+                break;
+            }
+            items.extend(batch);
+            if total.is_some_and(|total| items.len() >= total) {
+                break;
+            }
+        }
+
+        Ok(items)
+    }
+
+    #[tracing::instrument]
+    pub async fn user(&self) -> Result<GiteaUser> {
+        self.get("/user", &[]).await
+    }
+
+    /// Repositories the token can access; the ones owned by someone else are
+    /// only kept when contributed repositories are included
+    #[tracing::instrument]
+    pub async fn repositories(&self, user: &GiteaUser) -> Result<Vec<Repository>> {
+        let repos = self.get_paginated::<GiteaRepo>("/user/repos").await?;
+
+        let kept = repos
+            .iter()
+            .filter_map(|repo| {
+                let owned = repo.owner().eq_ignore_ascii_case(user.login());
+                match (owned, self.configuration.include_contributed_repos()) {
+                    (true, _) => Some((repo, RepoSource::Owned)),
+                    (false, true) => Some((repo, RepoSource::ContributedTo)),
+                    (false, false) => None,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let mut organizations = HashSet::new();
+        let owners = kept
+            .iter()
+            .map(|(repo, _)| repo.owner().to_lowercase())
+            .filter(|owner| !owner.eq_ignore_ascii_case(user.login()))
+            .collect::<BTreeSet<_>>();
+        for owner in owners {
+            if self.is_organization(&owner).await? {
+                organizations.insert(owner);
+            }
+        }
+
+        let mut set = RepositorySet::default();
+        set.extend(kept.into_iter().map(|(repo, source)| {
+            let in_organization = organizations.contains(&repo.owner().to_lowercase());
+            self.repository(repo, in_organization, source)
+        }));
+
+        let filter = self.configuration.repo_filter();
+        let mut repositories = set
+            .into_iter()
+            .filter(|repo| filter.matches(repo))
+            .collect::<Vec<_>>();
+
+        for repo in repositories.iter_mut() {
+            let languages = self
+                .languages(repo.name_with_owner())
+                .await
+                .unwrap_or_else(|e| {
+                    tracing::warn!(
+                        "Failed to fetch languages of {}: {:?}",
+                        repo.name_with_owner(),
+                        e
+                    );
+                    vec![]
+                });
+            repo.set_languages(languages);
+        }
+
+        Ok(repositories)
+    }
+
+    /// Gitea reports every repository owner as a user, so organizations are told
+    /// apart by looking the owner up among them
+    async fn is_organization(&self, login: &str) -> Result<bool> {
+        match self.get::<IgnoredAny>(&format!("/orgs/{login}"), &[]).await {
+            Ok(_) => Ok(true),
+            Err(e) if matches!(e.downcast_ref(), Some(StatsError::NotFound(_))) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Bytes of code per language, like GitHub's linguist sizes
+    async fn languages(&self, full_name: &str) -> Result<Vec<Language>> {
+        let languages = self
+            .get::<HashMap<String, i64>>(&format!("/repos/{full_name}/languages"), &[])
+            .await?;
+
+        Ok(languages
+            .into_iter()
+            .map(|(name, size)| {
+                let color = Language::fallback_color(&name);
+                Language::new(name, size, 1, color, 0.0)
+            })
+            .collect())
+    }
+
+    /// Daily contribution counts from the user heatmap, which covers the past year
+    #[tracing::instrument]
+    pub async fn contribution_counts(&self, user: &GiteaUser) -> Result<BTreeMap<NaiveDate, i64>> {
+        let heatmap = self
+            .get::<Vec<GiteaHeatmapEntry>>(&format!("/users/{}/heatmap", user.login()), &[])
+            .await?;
+
+        Ok(heatmap.iter().fold(BTreeMap::new(), |mut acc, entry| {
+            if let Some(date) = entry.date() {
+                *acc.entry(date).or_insert(0) += entry.contributions();
+            }
+            acc
+        }))
+    }
+
+    fn repository(
+        &self,
+        repo: &GiteaRepo,
+        in_organization: bool,
+        source: RepoSource,
+    ) -> Repository {
+        Repository::new(
+            repo.id().to_string(),
+            repo.full_name().to_string(),
+            repo.stars_count(),
+            repo.forks_count(),
+            repo.fork(),
+            repo.archived(),
+            repo.private(),
+            in_organization,
+            repo.updated_at(),
+            vec![],
+            source,
+        )
+    }
+}
+
+impl Forge for Gitea {
+    fn provider(&self) -> &'static str {
+        "gitea"
+    }
+
+    #[tracing::instrument]
+    async fn collect_stats(&self) -> Result<Stats> {
+        let user = self.user().await?;
+        let repositories = self.repositories(&user).await?;
+        tracing::info!("Collected {} Gitea repositories", repositories.len());
+
+        let forks = repositories.iter().map(Repository::forks).sum::<i64>();
+        let stargazers = repositories.iter().map(Repository::stargazers).sum::<i64>();
+        let languages = top_languages(&repositories);
+        let counts = Metric::from_result(
+            "Contributions",
+            self.contribution_counts(&user).await,
+            self.configuration.allow_partial_results(),
+        )?;
+        let today = Utc::now().date_naive();
+
+        let stats = StatsBuilder::default()
+            .name(user.name().to_string())
+            .avatar_url(user.avatar_url().map(str::to_string))
+            .total_contributions(counts.clone().map(|counts| counts.values().sum::<i64>()))
+            .views(Metric::Unavailable(
+                "Gitea doesn't report repository views".to_string(),
+            ))
+            .reviews(Metric::Unavailable(
+                "Gitea doesn't report reviews".to_string(),
+            ))
+            .lines_changed_by_repo(Metric::Unavailable(
+                "Gitea doesn't report lines changed".to_string(),
+            ))
+            .repos(repositories)
+            .forks(forks)
+            .stargazers(stargazers)
+            .languages(languages)
+            .languages_limit(self.configuration.languages_limit())
+            .contribution_calendar(counts.map(|counts| calendar_from_counts(&counts, today)))
+            .build()?;
+
+        Ok(stats)
+    }
+}
+
+fn malformed(path: &str, error: reqwest::Error) -> StatsError {
+    StatsError::Schema {
+        message: format!("malformed Gitea response from {path}"),
+        source: Some(error.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use axum::{extract::Query, http::HeaderMap, routing::get, Json, Router};
+    use secrecy::SecretString;
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::service::{test_support::stand_in, Credentials};
+
+    /// Items per page of the stand-in, below `PAGE_SIZE` like a small `MAX_RESPONSE_ITEMS`
+    const MAX_RESPONSE_ITEMS: usize = 2;
+
+    /// A stand-in Gitea owning five repositories, counting the pages requested
+    async fn gitea_server(total_count: bool) -> (String, Arc<AtomicUsize>) {
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counter = requests.clone();
+        let repos = (1..=5)
+            .map(|id| {
+                json!({
+                    "id": id,
+                    "full_name": format!("jane/repo{id}"),
+                    "owner": { "login": "jane" },
+                    "stars_count": id,
+                    "updated_at": "2026-10-01T00:00:00Z",
+                })
+            })
+            .collect::<Vec<_>>();
+        let app = Router::new()
+            .route(
+                "/api/v1/user",
+                get(|| async { Json(json!({ "id": 7, "login": "jane", "full_name": "" })) }),
+            )
+            .route(
+                "/api/v1/user/repos",
+                get(
+                    move |Query(query): Query<HashMap<String, usize>>| async move {
+                        counter.fetch_add(1, Ordering::SeqCst);
+                        let limit = query["limit"].min(MAX_RESPONSE_ITEMS);
+                        let page = repos
+                            .iter()
+                            .skip((query["page"] - 1) * limit)
+                            .take(limit)
+                            .cloned()
+                            .collect::<Vec<_>>();
+                        let mut headers = HeaderMap::new();
+                        if total_count {
+                            headers.insert("x-total-count", repos.len().into());
+                        }
+                        (headers, Json(page))
+                    },
+                ),
+            )
+            .route(
+                "/api/v1/repos/jane/{name}/languages",
+                get(|| async { Json(json!({ "Go": 300 })) }),
+            )
+            .route(
+                "/api/v1/users/jane/heatmap",
+                get(|| async {
+                    let now = Utc::now().timestamp();
+                    Json(json!([
+                        { "timestamp": now, "contributions": 2 },
+                        { "timestamp": now - 86_400, "contributions": 3 },
+                    ]))
+                }),
+            );
+        let url = stand_in(app).await;
+
+        (url, requests)
+    }
+
+    fn gitea(url: String) -> Gitea {
+        let configuration = Configuration::from_json(json!({
+            "github_actor": "jane",
+            "gitea_url": url,
+        }));
+        let auth = Auth::new(
+            Credentials::Token(SecretString::from("gitea-token")),
+            Client::new(),
+            url,
+        );
+
+        Gitea::new(configuration, Client::new(), auth)
+    }
+
+    #[tokio::test]
+    async fn short_pages_are_not_taken_for_the_last_one() {
+        let (url, requests) = gitea_server(false).await;
+
+        let repos = gitea(url)
+            .get_paginated::<Value>("/user/repos")
+            .await
+            .unwrap();
+
+        assert_eq!(repos.len(), 5);
+        // three pages of items and the empty one ending the listing
+        assert_eq!(requests.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn total_count_header_saves_the_empty_page() {
+        let (url, requests) = gitea_server(true).await;
+
+        let repos = gitea(url)
+            .get_paginated::<Value>("/user/repos")
+            .await
+            .unwrap();
+
+        assert_eq!(repos.len(), 5);
+        assert_eq!(requests.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn collects_the_stats_of_the_user() {
+        let (url, _) = gitea_server(true).await;
+
+        let stats = gitea(url).collect_stats().await.unwrap();
+
+        assert_eq!(stats.name(), "jane");
+        assert_eq!(stats.repos().len(), 5);
+        assert_eq!(stats.stargazers(), 15);
+        assert_eq!(stats.total_contributions(), Some(5));
+        assert_eq!(stats.languages()[0].1.bytes(), Some(1500));
+        assert_eq!(stats.views(), None);
+        assert_eq!(stats.reviews(), None);
+    }
+
+    #[tokio::test]
+    async fn only_repositories_of_organizations_are_organization_owned() {
+        let repos = ["Jane/app", "acme/platform", "john/dotfiles"]
+            .into_iter()
+            .enumerate()
+            .map(|(id, full_name)| {
+                let (owner, _) = full_name.split_once('/').unwrap();
+                json!({ "id": id, "full_name": full_name, "owner": { "login": owner } })
+            })
+            .collect::<Vec<_>>();
+        let app = Router::new()
+            .route(
+                "/api/v1/user",
+                get(|| async { Json(json!({ "id": 7, "login": "jane", "full_name": "" })) }),
+            )
+            .route(
+                "/api/v1/user/repos",
+                get(move || async move {
+                    let mut headers = HeaderMap::new();
+                    headers.insert("x-total-count", repos.len().into());
+                    (headers, Json(repos))
+                }),
+            )
+            // john is a user, so looking him up among the organizations finds nothing
+            .route(
+                "/api/v1/orgs/acme",
+                get(|| async { Json(json!({ "id": 9, "name": "acme" })) }),
+            )
+            .route(
+                "/api/v1/repos/{owner}/{name}/languages",
+                get(|| async { Json(json!({})) }),
+            );
+        let url = stand_in(app).await;
+        let configuration = Configuration::from_json(json!({
+            "github_actor": "jane",
+            "gitea_url": url,
+            "include_contributed_repos": true,
+        }));
+        let auth = Auth::new(
+            Credentials::Token(SecretString::from("gitea-token")),
+            Client::new(),
+            url,
+        );
+        let gitea = Gitea::new(configuration, Client::new(), auth);
+
+        let user = gitea.user().await.unwrap();
+        let repos = gitea.repositories(&user).await.unwrap();
+
+        let owned_by_organizations = repos
+            .iter()
+            .map(|repo| (repo.name_with_owner(), repo.is_in_organization()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            owned_by_organizations,
+            [
+                ("Jane/app", false),
+                ("acme/platform", true),
+                ("john/dotfiles", false)
+            ]
+        );
+    }
+}
@@ -1,284 +1,177 @@
-use std::collections::HashMap;
+use std::collections::HashSet;
 use std::time::Duration;
 
 use anyhow::Result;
 use chrono::{DateTime, NaiveDate, TimeZone, Utc};
-use graphql_client::reqwest::post_graphql;
-use reqwest::Client;
+use graphql_client::GraphQLQuery;
+use reqwest::{Client, StatusCode};
+use secrecy::ExposeSecret;
 use tokio::task::JoinSet;
 use tokio::time::sleep;
 
 use crate::{
-    algebra::GithubExt,
+    algebra::{Forge, GithubExt},
     domain::{
+        app_preflight,
         contribution_calendar::{
             self, ContributionCalendarUserContributionsCollectionContributionCalendarWeeks,
+            ContributionLevel,
         },
-        contribution_years, contributions_by_year, repos_overview, ContributionCalendar,
-        ContributionYears, ContributionsByYear, ContributorActivity, Language, ReposOverview,
-        Stats, StatsBuilder, ViewTraffic,
+        contribution_years, contributions_by_year, org_overview, preflight, pull_request_reviews,
+        repos_overview, top_languages, AppPreflight, ContributionCalendar, ContributionDay,
+        ContributionWeek, ContributionYears, ContributionsByYear, ContributorActivity, DailyViews,
+        Language, LinesChangedByRepo, Metric, MetricAvailability, OrgOverview, Preflight,
+        PreflightReport, PullRequestReviews, RateLimit, RepoHistory, RepoSource, ReposOverview,
+        Repository, RepositorySet, Stats, StatsBuilder, StatsChange, StatsError, ViewTraffic,
     },
 };
 
-use super::Configuration;
+use super::{
+    analyze_clones, check_status, rate_limit_reset, Auth, Configuration, Credentials, GitHistory,
+    LinesChangedBackend, ResponseCache,
+};
 
 #[derive(Debug)]
 pub struct Github {
     configuration: Configuration,
     client: Client,
+    auth: Auth,
+    cache: ResponseCache,
 }
 
 impl Github {
-    pub fn new(configuration: Configuration, client: Client) -> Self {
+    pub fn new(configuration: Configuration, client: Client, auth: Auth) -> Self {
         Self {
             configuration,
             client,
+            auth,
+            cache: ResponseCache::default(),
         }
     }
 
-    pub fn graphql_url(&self) -> String {
-        format!("{}/graphql", self.configuration.github_url())
+    /// Shares REST responses with other `Github` instances using the same cache
+    pub fn with_cache(mut self, cache: ResponseCache) -> Self {
+        self.cache = cache;
+        self
     }
-}
-
-impl GithubExt for Github {
-    type CalendarWeek = ContributionCalendarUserContributionsCollectionContributionCalendarWeeks;
-
-    #[tracing::instrument]
-    async fn total_contributions(&self) -> Result<i64, anyhow::Error> {
-        let variables = contribution_years::Variables {};
-
-        let contribution_years_response =
-            post_graphql::<ContributionYears, _>(&self.client, &self.graphql_url(), variables)
-                .await?;
-
-        let years = contribution_years_response
-            .data
-            .map(|data| data.viewer.contributions_collection.contribution_years);
-
-        let variables = years
-            .map(|years| {
-                years
-                    .iter()
-                    .filter_map(|year| {
-                        let year_i32 = *year as i32;
 
-                        let beggining_of_the_year: Option<DateTime<Utc>> =
-                            NaiveDate::from_ymd_opt(year_i32, 1, 1)
-                                .and_then(|date| date.and_hms_opt(0, 0, 0))
-                                .map(|naive_date_time| {
-                                    TimeZone::from_utc_datetime(&Utc, &naive_date_time)
-                                });
-
-                        let beggining_of_the_next_year: Option<DateTime<Utc>> =
-                            NaiveDate::from_ymd_opt(year_i32 + 1, 1, 1)
-                                .and_then(|date| date.and_hms_opt(0, 0, 0))
-                                .map(|naive_date_time| {
-                                    TimeZone::from_utc_datetime(&Utc, &naive_date_time)
-                                });
+    pub fn graphql_url(&self) -> String {
+        self.configuration.graphql_url()
+    }
 
-                        beggining_of_the_year
-                            .zip(beggining_of_the_next_year)
-                            .map(|(start, end)| contributions_by_year::Variables {
-                                from: start.to_rfc3339(),
-                                to: end.to_rfc3339(),
-                            })
-                    })
-                    .collect::<Vec<_>>()
-            })
-            .unwrap_or_default();
+    async fn post_graphql<Q: GraphQLQuery>(
+        &self,
+        variables: Q::Variables,
+    ) -> Result<graphql_client::Response<Q::ResponseData>, StatsError> {
+        let token = self.auth.token().await?;
+        let response = self
+            .client
+            .post(self.graphql_url())
+            .bearer_auth(token.expose_secret())
+            .json(&Q::build_query(variables))
+            .send()
+            .await?;
+        let response = check_status(response, "GraphQL API").await?;
+        let reset_at = rate_limit_reset(response.headers());
 
-        let mut result = vec![];
-        // why I can't use JoinSet here?
+        let response = response
+            .json::<graphql_client::Response<Q::ResponseData>>()
+            .await
+            .map_err(|e| StatsError::Schema {
+                message: "malformed GraphQL response".to_string(),
+                source: Some(e.into()),
+            })?;
 
-        // I had an error when I tried to use JoinSet here, so I had to use a for loop
-        // temporary v
-        for variables in variables {
-            let response = post_graphql::<ContributionsByYear, _>(
-                &self.client,
-                &self.graphql_url(),
-                variables,
-            )
-            .await;
-            result.push(response);
+        if let Some(errors) = response.errors.as_ref().filter(|errors| !errors.is_empty()) {
+            let messages = errors
+                .iter()
+                .map(|error| error.message.as_str())
+                .collect::<Vec<_>>()
+                .join("; ");
+            if messages.to_lowercase().contains("rate limit") {
+                return Err(StatsError::RateLimited { reset_at });
+            }
+            if messages.contains("Could not resolve to") {
+                return Err(StatsError::NotFound(messages));
+            }
+            if response.data.is_none() {
+                return Err(StatsError::schema(messages));
+            }
+            tracing::warn!("GraphQL API returned partial data: {}", messages);
         }
 
-        let by_year_response = result
-            .into_iter()
-            .filter_map(Result::ok)
-            .filter_map(|response| response.data.map(|data| data.viewer))
-            .collect::<Vec<_>>();
-
-        let total_contributions: i64 = by_year_response
-            .iter()
-            .map(|by_year| {
-                by_year
-                    .contributions_collection
-                    .contribution_calendar
-                    .total_contributions
-            })
-            .sum();
+        Ok(response)
+    }
 
-        Ok(total_contributions)
+    fn filter_repositories(&self, repositories: RepositorySet) -> Vec<Repository> {
+        let filter = self.configuration.repo_filter();
+        repositories
+            .into_iter()
+            .filter(|repo| filter.matches(repo))
+            .collect()
     }
 
+    /// The user's name and the filtered repositories every metric is derived from
     #[tracing::instrument]
-    async fn get_stats(&self) -> Result<Stats> {
+    async fn repositories(&self) -> Result<(String, Vec<Repository>), StatsError> {
         let mut next_owned = None;
         let mut next_contrib = None;
 
         let mut name = None;
-        let mut repos: Vec<String> = vec![];
-        let mut forks = 0;
-        let mut stargazers = 0;
-        let mut languages_map: HashMap<String, Language> = HashMap::new();
+        let mut repositories = RepositorySet::default();
+        let include_contributed = self.configuration.include_contributed_repos();
 
         loop {
             let variables = repos_overview::Variables {
+                login: self.configuration.github_actor().to_string(),
                 owned_cursor: next_owned,
                 contributed_cursor: next_contrib,
             };
-            let raw_results =
-                post_graphql::<ReposOverview, _>(&self.client, &self.graphql_url(), variables)
-                    .await?;
-
-            name = name.or(raw_results
+            let raw_results = self.post_graphql::<ReposOverview>(variables).await?;
+            let user = raw_results
                 .data
                 .as_ref()
-                .and_then(|data| data.viewer.name.clone()));
-
-            let mut languages_contributed = languages_map;
+                .and_then(|data| data.user.as_ref());
 
-            if self.configuration.exclude_forked_repos() {
-                // in this case we only fetch owned repos
-            } else {
-                // in this case we fetch both owned and contributed repos
-                let contributed_repos = raw_results
-                    .data
-                    .as_ref()
-                    .and_then(|data| data.viewer.repositories_contributed_to.nodes.as_ref())
-                    .map(|nodes| {
-                        nodes
-                            .iter()
-                            .filter(|opt| opt.is_some())
-                            .flatten()
-                            .collect::<Vec<_>>()
-                    });
+            name = name.or(user.and_then(|user| user.name.clone()));
 
-                for repo in contributed_repos.iter().flatten() {
-                    repos.push(repo.name_with_owner.clone());
-                    forks += repo.fork_count;
-                    stargazers += repo.stargazers.total_count;
-                }
+            let owned_repos = user.map(|user| &user.repositories);
 
-                languages_contributed = contributed_repos
+            repositories.extend(
+                owned_repos
                     .iter()
-                    .flatten()
-                    .filter_map(|repo| repo.languages.as_ref())
-                    .filter_map(|languages| languages.edges.as_ref())
+                    .flat_map(|repos| &repos.nodes)
                     .flatten()
                     .flatten()
-                    .map(|edge| {
-                        Language::new(
-                            edge.node.name.clone(),
-                            edge.size,
-                            1,
-                            edge.node.color.clone().unwrap_or("#000000".to_string()),
-                            0.0,
-                        )
-                    })
-                    .collect::<Vec<_>>()
-                    .iter()
-                    .fold(HashMap::new(), |mut acc, lang| {
-                        acc.entry(lang.name().to_string())
-                            .and_modify(|e: &mut Language| {
-                                let new_zise: i64 = e.size() + lang.size();
-                                let new_occurences: i64 = e.occurrences() + lang.occurrences();
-                                e.set_occurrences(new_occurences);
-                                e.set_size(new_zise);
-                            })
-                            .or_insert(lang.clone());
-                        acc
-                    });
-            }
-
-            let owned_repos = raw_results
-                .data
-                .as_ref()
-                .map(|data| &data.viewer.repositories);
+                    .map(Repository::from),
+            );
 
-            for repo in owned_repos
-                .iter()
-                .flat_map(|repos| &repos.nodes)
-                .flatten()
-                .flatten()
-            {
-                repos.push(repo.name_with_owner.clone());
-                forks += repo.fork_count;
-                stargazers += repo.stargazer_count;
+            if include_contributed {
+                repositories.extend(
+                    user.and_then(|user| user.repositories_contributed_to.nodes.as_ref())
+                        .into_iter()
+                        .flatten()
+                        .flatten()
+                        .map(Repository::from),
+                );
             }
 
-            languages_map = owned_repos
-                .iter()
-                .flat_map(|repos| &repos.nodes)
-                .flatten()
-                .flatten()
-                .filter_map(|nodes| nodes.languages.as_ref())
-                .filter_map(|languages| languages.edges.as_ref())
-                .flatten()
-                .flatten()
-                .map(|edge| {
-                    Language::new(
-                        edge.node.name.clone(),
-                        edge.size,
-                        1,
-                        edge.node.color.clone().unwrap_or("#000000".to_string()),
-                        0.0,
-                    )
-                })
-                .collect::<Vec<_>>()
-                .iter()
-                .fold(languages_contributed, |mut acc, lang| {
-                    acc.entry(lang.name().to_string())
-                        .and_modify(|e: &mut Language| {
-                            let new_zise: i64 = e.size() + lang.size();
-                            let new_occurences: i64 = e.occurrences() + lang.occurrences();
-                            e.set_occurrences(new_occurences);
-                            e.set_size(new_zise);
-                        })
-                        .or_insert(lang.clone());
-                    acc
-                });
-
-            let total_size = languages_map.values().map(|lang| lang.size()).sum::<i64>();
-            languages_map.iter_mut().for_each(|(_, lang)| {
-                lang.set_proportion(total_size);
-            });
-
             let has_next_owned = owned_repos
                 .as_ref()
                 .map(|repos| repos.page_info.has_next_page)
                 .unwrap_or_default();
 
-            let has_next_contrib = !self.configuration.exclude_forked_repos()
-                && raw_results
-                    .data
-                    .as_ref()
-                    .map(|data| {
-                        data.viewer
-                            .repositories_contributed_to
-                            .page_info
-                            .has_next_page
-                    })
+            let has_next_contrib = include_contributed
+                && user
+                    .map(|user| user.repositories_contributed_to.page_info.has_next_page)
                     .unwrap_or_default();
 
             if has_next_owned || has_next_contrib {
                 next_owned = owned_repos
                     .as_ref()
                     .and_then(|repos| repos.page_info.end_cursor.as_ref().cloned());
-                next_contrib = raw_results.data.as_ref().and_then(|data| {
-                    data.viewer
-                        .repositories_contributed_to
+                next_contrib = user.and_then(|user| {
+                    user.repositories_contributed_to
                         .page_info
                         .end_cursor
                         .as_ref()
@@ -288,68 +181,53 @@ impl GithubExt for Github {
                 break;
             }
         }
-        // sort languages by size and take top N languages as defined in configuration
-        let mut languages = languages_map.into_iter().collect::<Vec<_>>();
-        languages.sort_by_key(|b| std::cmp::Reverse(b.1.size()));
-        languages = languages
-            .into_iter()
-            .take(self.configuration.languages_limit())
-            .collect();
-        let total_contributions = self.total_contributions();
-        let views = self.views(&repos);
-        let lines_changed = self.lines_changed(&repos);
-        let calendar = self.contribution_calendar();
-
-        let (total_contributions, views, lines_changed, calendar) =
-            tokio::join!(total_contributions, views, lines_changed, calendar);
 
-        let stats = StatsBuilder::default()
-            .name(name.unwrap_or_default())
-            .total_contributions(total_contributions?)
-            .views(views?)
-            .lines_changed(lines_changed?)
-            .repos(repos)
-            .forks(forks)
-            .stargazers(stargazers)
-            .languages(languages)
-            .contribution_calendar(calendar?)
-            .build()?;
+        // every repository-based metric is derived from the same filtered set
+        let repositories = self.filter_repositories(repositories);
+        tracing::info!(
+            "Collected {} repositories, {} of them found in more than one list",
+            repositories.len(),
+            repositories
+                .iter()
+                .filter(|repo| repo.sources().len() > 1)
+                .count()
+        );
 
-        Ok(stats)
+        Ok((name.unwrap_or_default(), repositories))
     }
 
-    #[tracing::instrument]
-    async fn views(&self, repos: &[String]) -> Result<i64> {
-        let mut views = 0;
-
-        for repo in repos {
-            let response = self
-                .client
-                .get(format!(
-                    "{}/repos/{}/traffic/views",
-                    self.configuration.github_url(),
-                    repo
-                ))
-                .send()
-                .await?;
-            let json = response.json::<ViewTraffic>().await?;
-            let sum: i64 = json.views().iter().map(|view| view.count()).sum();
-            views += sum;
-        }
-        Ok(views)
+    /// Collects the user's history from the local clones of `repos`
+    pub async fn local_history(&self, repos: &[String]) -> Result<Vec<RepoHistory>> {
+        analyze_clones(
+            &self.configuration,
+            Some(repos),
+            GitHistory::for_user(&self.configuration),
+        )
+        .await
     }
 
     #[tracing::instrument]
-    async fn lines_changed(&self, repos: &[String]) -> Result<(i64, i64)> {
-        tracing::debug!("Starting lines_changed for repos: {:?}", repos);
+    pub async fn contributor_activity(
+        &self,
+        repos: &[String],
+    ) -> Result<Vec<(String, Vec<ContributorActivity>)>> {
+        tracing::debug!("Starting contributor_activity for repos: {:?}", repos);
 
         // Limit concurrent requests to avoid overwhelming the API
         let semaphore = std::sync::Arc::new(tokio::sync::Semaphore::new(5));
         let mut tasks = JoinSet::new();
+        let mut activities = Vec::new();
 
         for repo in repos {
+            if let Some(cached) = self.cache.contributors(repo) {
+                tracing::debug!("Using cached contributor stats for repo {}", repo);
+                activities.push((repo.clone(), cached));
+                continue;
+            }
+
             let repo = repo.clone();
             let client = self.client.clone();
+            let token = self.auth.token().await?;
             let url = format!(
                 "{}/repos/{}/stats/contributors",
                 self.configuration.github_url(),
@@ -362,7 +240,7 @@ impl GithubExt for Github {
                 // Acquire semaphore permit to limit concurrency
                 let _permit = semaphore.acquire().await.unwrap();
 
-                let result: anyhow::Result<Vec<ContributorActivity>> = async {
+                let result: Result<Vec<ContributorActivity>, StatsError> = async {
                     // Retry logic with exponential backoff
                     let max_retries = 3;
                     let mut retry_count = 0;
@@ -370,43 +248,12 @@ impl GithubExt for Github {
                     loop {
                         let response = client
                             .get(&url)
+                            .bearer_auth(token.expose_secret())
                             .send()
-                            .await
-                            .map_err(|e| {
-                                tracing::error!("HTTP request failed for repo {}: {:?}", repo, e);
-                                anyhow::anyhow!("HTTP request failed for repo {}: {}", repo, e)
-                            })?;
+                            .await?;
 
-                        let status = response.status();
-
-                        // Handle different status codes
-                        match status.as_u16() {
-                            200 => {
-                                // Success - parse the response
-                                let text = response.text().await.map_err(|e| {
-                                    tracing::error!("Failed to get response text for repo {}: {:?}", repo, e);
-                                    anyhow::anyhow!("Failed to get response text for repo {}: {}", repo, e)
-                                })?;
-
-                                let data = serde_json::from_str::<Vec<ContributorActivity>>(&text)
-                                    .map_err(|e| {
-                                        tracing::error!(
-                                            "Failed to parse JSON for repo {} (status: {}): {:?}\nResponse body: {}",
-                                            repo,
-                                            status,
-                                            e,
-                                            text
-                                        );
-                                        anyhow::anyhow!("Failed to parse JSON for repo {}: {}", repo, e)
-                                    })?;
-
-                                tracing::debug!(
-                                    "Successfully fetched contributor stats for repo {}",
-                                    repo
-                                );
-                                return Ok(data);
-                            }
-                            202 => {
+                        match response.status() {
+                            StatusCode::ACCEPTED => {
                                 // Stats are being computed - retry after delay
                                 if retry_count < max_retries {
                                     let delay = Duration::from_secs(2u64.pow(retry_count));
@@ -420,106 +267,664 @@ impl GithubExt for Github {
                                     retry_count += 1;
                                     sleep(delay).await;
                                     continue;
-                                } else {
-                                    tracing::error!(
-                                        "Max retries exceeded for repo {} - stats still being computed",
-                                        repo
-                                    );
-                                    return Ok(Vec::new()); // Return empty vec to continue with other repos
                                 }
+                                // an empty list would read as no lines changed, and stick in the cache
+                                return Err(StatsError::Other(anyhow::anyhow!(
+                                    "GitHub is still computing the contributor statistics of {}",
+                                    repo
+                                )));
                             }
-                            403 | 429 => {
-                                // Rate limited
-                                let text = response.text().await.unwrap_or_default();
-                                tracing::error!(
-                                    "Rate limited for repo {} (status: {}): {}",
-                                    repo,
-                                    status,
-                                    text
-                                );
-                                return Err(anyhow::anyhow!(
-                                    "Rate limited for repo {}: {}",
-                                    repo,
-                                    text
-                                ));
-                            }
-                            404 => {
-                                // Repo not found or no stats available
-                                tracing::warn!("Repository not found or no stats: {} (404)", repo);
+                            // empty repositories have no statistics, and neither do ones gone since listed
+                            StatusCode::NO_CONTENT | StatusCode::NOT_FOUND => {
+                                tracing::warn!("No contributor statistics for {}", repo);
                                 return Ok(Vec::new());
                             }
-                            _ => {
-                                // Other error statuses
-                                let text = response.text().await.unwrap_or_default();
-                                tracing::error!(
-                                    "Unexpected status {} for repo {}: {}",
-                                    status,
-                                    repo,
-                                    text
-                                );
-                                return Err(anyhow::anyhow!(
-                                    "HTTP {} for repo {}: {}",
-                                    status,
-                                    repo,
-                                    text
-                                ));
-                            }
+                            _ => {}
                         }
+
+                        let response =
+                            check_status(response, &format!("contributor statistics of {repo}"))
+                                .await?;
+                        let data = response
+                            .json::<Vec<ContributorActivity>>()
+                            .await
+                            .map_err(|e| StatsError::Schema {
+                                message: format!("contributor statistics of {repo}"),
+                                source: Some(e.into()),
+                            })?;
+
+                        tracing::debug!(
+                            "Successfully fetched contributor stats for repo {}",
+                            repo
+                        );
+                        return Ok(data);
                     }
                 }
                 .await;
 
-                result
+                result.map(|activities| (repo, activities))
             });
         }
 
-        let mut all_activities = Vec::new();
         while let Some(res) = tasks.join_next().await {
             match res {
-                Ok(Ok(contributors)) => {
-                    tracing::debug!("Fetched {} contributors", contributors.len());
-                    all_activities.extend(contributors);
+                Ok(Ok((repo, contributors))) => {
+                    tracing::debug!("Fetched {} contributors for {}", contributors.len(), repo);
+                    self.cache.insert_contributors(&repo, contributors.clone());
+                    activities.push((repo, contributors));
                 }
+                // the statistics would be missing a repository, the first failure fails them all
                 Ok(Err(e)) => {
-                    tracing::error!("Task failed with error: {:?}", e);
+                    tasks.abort_all();
+                    return Err(e.into());
                 }
                 Err(e) => {
-                    tracing::error!("Join error: {:?}", e);
+                    tasks.abort_all();
+                    return Err(e.into());
                 }
             }
         }
+        activities.sort_by(|a, b| a.0.cmp(&b.0));
 
-        let res = all_activities.iter().fold((0, 0), |acc, activity| {
-            (
-                acc.0
-                    + activity
-                        .weeks()
-                        .iter()
-                        .map(|week| week.added())
-                        .sum::<i64>(),
-                acc.1
-                    + activity
-                        .weeks()
+        Ok(activities)
+    }
+}
+
+impl Forge for Github {
+    fn provider(&self) -> &'static str {
+        "github"
+    }
+
+    async fn collect_stats(&self) -> Result<Stats> {
+        match self.configuration.organization() {
+            Some(org) => Ok(self.get_org_stats(org).await?),
+            None => Ok(self.get_stats().await?),
+        }
+    }
+
+    #[tracing::instrument(skip(stats))]
+    async fn refresh(&self, stats: &Stats, changes: &[StatsChange]) -> Result<Stats> {
+        // organization statistics aggregate every member's activity, there's no smaller part
+        if self.configuration.organization().is_some() || changes.contains(&StatsChange::Everything)
+        {
+            return self.collect_stats().await;
+        }
+
+        let allow_partial = self.configuration.allow_partial_results();
+        let mut stats = stats.clone();
+        for change in changes {
+            match change {
+                StatsChange::Repositories => {
+                    let (_, repositories) = self.repositories().await?;
+                    stats.set_repos(repositories);
+                }
+                StatsChange::LinesChanged(repo) => {
+                    if !stats
+                        .repos()
                         .iter()
-                        .map(|week| week.deleted())
-                        .sum::<i64>(),
+                        .any(|known| known.name_with_owner().eq_ignore_ascii_case(repo))
+                    {
+                        tracing::debug!("{} isn't counted, skipping its lines changed", repo);
+                        continue;
+                    }
+                    let repos = [repo.clone()];
+                    match self.configuration.lines_changed_backend() {
+                        LinesChangedBackend::Api => {
+                            for (repo, changed) in self.lines_changed(&repos).await? {
+                                stats.set_lines_changed(&repo, changed);
+                            }
+                        }
+                        LinesChangedBackend::Git => {
+                            for history in self.local_history(&repos).await? {
+                                stats.set_repo_history(history);
+                            }
+                        }
+                    }
+                }
+                StatsChange::Contributions => {
+                    let (total_contributions, reviews, calendar) = tokio::join!(
+                        self.total_contributions(),
+                        self.reviews(),
+                        self.contribution_calendar()
+                    );
+                    stats.set_total_contributions(Metric::from_result(
+                        "Contributions",
+                        total_contributions,
+                        allow_partial,
+                    )?);
+                    stats.set_reviews(Metric::from_result("Reviews", reviews, allow_partial)?);
+                    stats.set_contribution_calendar(Metric::from_result(
+                        "Contribution calendar",
+                        calendar,
+                        allow_partial,
+                    )?);
+                }
+                StatsChange::Everything => unreachable!("handled above"),
+            }
+        }
+
+        Ok(stats)
+    }
+}
+
+impl GithubExt for Github {
+    #[tracing::instrument]
+    async fn preflight(&self) -> Result<PreflightReport, StatsError> {
+        let is_app = matches!(self.auth.credentials(), Credentials::App { .. });
+        let token = self.auth.token().await?;
+
+        // installation tokens can't read /user, and don't carry scopes either
+        let scopes = if is_app {
+            None
+        } else {
+            let response = self
+                .client
+                .get(format!("{}/user", self.configuration.github_url()))
+                .bearer_auth(token.expose_secret())
+                .send()
+                .await?;
+            check_status(response, "/user")
+                .await?
+                .headers()
+                .get("x-oauth-scopes")
+                .and_then(|scopes| scopes.to_str().ok())
+                .map(|scopes| {
+                    scopes
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|scope| !scope.is_empty())
+                        .map(str::to_string)
+                        .collect::<Vec<_>>()
+                })
+        };
+
+        // an installation acts as no user, so there's no viewer to check the actor against
+        let (viewer_login, rate_limit) = if is_app {
+            let data = self
+                .post_graphql::<AppPreflight>(app_preflight::Variables {})
+                .await?
+                .data
+                .ok_or_else(|| {
+                    StatsError::schema("the GraphQL API returned no data for the token")
+                })?;
+            let rate_limit = data.rate_limit.map(|rate_limit| {
+                preflight_rate_limit(rate_limit.limit, rate_limit.remaining, &rate_limit.reset_at)
+            });
+            (None, rate_limit)
+        } else {
+            let data = self
+                .post_graphql::<Preflight>(preflight::Variables {})
+                .await?
+                .data
+                .ok_or_else(|| {
+                    StatsError::schema("the GraphQL API returned no data for the token")
+                })?;
+            let rate_limit = data.rate_limit.map(|rate_limit| {
+                preflight_rate_limit(rate_limit.limit, rate_limit.remaining, &rate_limit.reset_at)
+            });
+            (Some(data.viewer.login), rate_limit)
+        };
+        let actor = self.configuration.github_actor().to_string();
+        let rate_limit = rate_limit.unwrap_or_else(|| RateLimit::new(0, 0, Utc::now()));
+
+        // `None` when the token type doesn't report scopes
+        let has_scope = |scope: &str| {
+            scopes
+                .as_ref()
+                .map(|scopes| scopes.iter().any(|granted| granted == scope))
+        };
+        let actor_matches = viewer_login
+            .as_ref()
+            .is_none_or(|login| login.eq_ignore_ascii_case(&actor));
+        let owner = viewer_login.as_deref().unwrap_or_default();
+        let mut metrics = vec![];
+
+        metrics.push(match has_scope("repo") {
+            Some(false) => MetricAvailability::partial(
+                "Stars, forks, repositories, languages",
+                "token lacks the `repo` scope, private repositories are not counted".to_string(),
+            ),
+            _ => MetricAvailability::available("Stars, forks, repositories, languages"),
+        });
+
+        if let Some(org) = self.configuration.organization() {
+            if has_scope("read:org") == Some(false) {
+                metrics.push(MetricAvailability::partial(
+                    "Organization repositories",
+                    format!("token lacks the `read:org` scope, private repositories of {org} may be missing"),
+                ));
+            }
+        }
+
+        metrics.push(if is_app {
+            MetricAvailability::partial(
+                "Contributions, calendar, reviews",
+                format!(
+                    "only contributions of {actor} visible to the App installation are counted"
+                ),
+            )
+        } else if actor_matches {
+            MetricAvailability::available("Contributions, calendar, reviews")
+        } else {
+            MetricAvailability::partial(
+                "Contributions, calendar, reviews",
+                format!("token belongs to {owner}, private contributions of {actor} are hidden"),
             )
         });
 
+        metrics.push(match (has_scope("repo"), actor_matches) {
+            (Some(false), _) => MetricAvailability::unavailable(
+                "Repository views",
+                "traffic requires the `repo` scope".to_string(),
+            ),
+            (_, false) => MetricAvailability::unavailable(
+                "Repository views",
+                format!(
+                    "traffic requires push access, the token belongs to {owner} and not {actor}"
+                ),
+            ),
+            (None, true) => MetricAvailability::partial(
+                "Repository views",
+                "requires the `Administration: read` repository permission".to_string(),
+            ),
+            (Some(true), true) => MetricAvailability::available("Repository views"),
+        });
+
+        metrics.push(match self.configuration.lines_changed_backend() {
+            LinesChangedBackend::Git => MetricAvailability::partial(
+                "Lines changed",
+                "computed from local clones".to_string(),
+            ),
+            LinesChangedBackend::Api if has_scope("repo") == Some(false) => {
+                MetricAvailability::partial(
+                    "Lines changed",
+                    "only public repositories without the `repo` scope".to_string(),
+                )
+            }
+            LinesChangedBackend::Api => MetricAvailability::available("Lines changed"),
+        });
+
+        Ok(PreflightReport::new(
+            viewer_login,
+            actor,
+            scopes,
+            rate_limit,
+            metrics,
+        ))
+    }
+
+    #[tracing::instrument]
+    async fn total_contributions(&self) -> Result<i64, StatsError> {
+        let login = self.configuration.github_actor().to_string();
+        let variables = contribution_years::Variables {
+            login: login.clone(),
+        };
+
+        let contribution_years_response = self.post_graphql::<ContributionYears>(variables).await?;
+
+        let years = contribution_years_response
+            .data
+            .and_then(|data| data.user)
+            .map(|user| user.contributions_collection.contribution_years);
+
+        let variables = years
+            .map(|years| {
+                years
+                    .iter()
+                    .filter_map(|year| {
+                        let year_i32 = *year as i32;
+
+                        let beggining_of_the_year: Option<DateTime<Utc>> =
+                            NaiveDate::from_ymd_opt(year_i32, 1, 1)
+                                .and_then(|date| date.and_hms_opt(0, 0, 0))
+                                .map(|naive_date_time| {
+                                    TimeZone::from_utc_datetime(&Utc, &naive_date_time)
+                                });
+
+                        let beggining_of_the_next_year: Option<DateTime<Utc>> =
+                            NaiveDate::from_ymd_opt(year_i32 + 1, 1, 1)
+                                .and_then(|date| date.and_hms_opt(0, 0, 0))
+                                .map(|naive_date_time| {
+                                    TimeZone::from_utc_datetime(&Utc, &naive_date_time)
+                                });
+
+                        beggining_of_the_year
+                            .zip(beggining_of_the_next_year)
+                            .map(|(start, end)| contributions_by_year::Variables {
+                                login: login.clone(),
+                                from: start.to_rfc3339(),
+                                to: end.to_rfc3339(),
+                            })
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+
+        let mut result = vec![];
+        // why I can't use JoinSet here?
+
+        // I had an error when I tried to use JoinSet here, so I had to use a for loop
+        // temporary v
+        for variables in variables {
+            // a missing year would silently lower the total
+            let response = self.post_graphql::<ContributionsByYear>(variables).await?;
+            result.push(response);
+        }
+
+        let by_year_response = result
+            .into_iter()
+            .filter_map(|response| response.data.and_then(|data| data.user))
+            .collect::<Vec<_>>();
+
+        let total_contributions: i64 = by_year_response
+            .iter()
+            .map(|by_year| {
+                by_year
+                    .contributions_collection
+                    .contribution_calendar
+                    .total_contributions
+            })
+            .sum();
+
+        Ok(total_contributions)
+    }
+
+    #[tracing::instrument]
+    async fn get_stats(&self) -> Result<Stats, StatsError> {
+        let (name, repositories) = self.repositories().await?;
+
+        let repo_names = repositories
+            .iter()
+            .map(|repo| repo.name_with_owner().to_string())
+            .collect::<Vec<_>>();
+        let forks = repositories.iter().map(Repository::forks).sum::<i64>();
+        let stargazers = repositories.iter().map(Repository::stargazers).sum::<i64>();
+        let languages = top_languages(&repositories);
+
+        let total_contributions = self.total_contributions();
+        let views = self.views(&repo_names);
+        let lines_changed = async {
+            match self.configuration.lines_changed_backend() {
+                LinesChangedBackend::Api => self
+                    .lines_changed(&repo_names)
+                    .await
+                    .map(|lines_changed| (lines_changed, vec![])),
+                LinesChangedBackend::Git => self
+                    .local_history(&repo_names)
+                    .await
+                    .map_err(StatsError::from)
+                    .map(|history| {
+                        let lines_changed = history
+                            .iter()
+                            .map(|repo| (repo.name().to_string(), (repo.added(), repo.deleted())))
+                            .collect();
+                        (lines_changed, history)
+                    }),
+            }
+        };
+        let reviews = self.reviews();
+        let calendar = self.contribution_calendar();
+
+        let (total_contributions, views, lines_changed, reviews, calendar) =
+            tokio::join!(total_contributions, views, lines_changed, reviews, calendar);
+
+        // the repositories are in hand, a metric failing from here on only degrades the cards
+        let allow_partial = self.configuration.allow_partial_results();
+        let lines_changed = Metric::from_result("Lines changed", lines_changed, allow_partial)?;
+        let history = lines_changed
+            .value()
+            .map(|(_, history)| history.clone())
+            .unwrap_or_default();
+
+        let stats = StatsBuilder::default()
+            .name(name)
+            .total_contributions(Metric::from_result(
+                "Contributions",
+                total_contributions,
+                allow_partial,
+            )?)
+            .views(Metric::from_result(
+                "Repository views",
+                views,
+                allow_partial,
+            )?)
+            .reviews(Metric::from_result("Reviews", reviews, allow_partial)?)
+            .lines_changed_by_repo(lines_changed.map(|(lines_changed, _)| lines_changed))
+            .history(history)
+            .repos(repositories)
+            .forks(forks)
+            .stargazers(stargazers)
+            .languages(languages)
+            .languages_limit(self.configuration.languages_limit())
+            .contribution_calendar(Metric::from_result(
+                "Contribution calendar",
+                calendar,
+                allow_partial,
+            )?)
+            .build()?;
+
+        Ok(stats)
+    }
+
+    #[tracing::instrument]
+    async fn get_org_stats(&self, org: &str) -> Result<Stats, StatsError> {
+        let mut cursor = None;
+        let mut name = None;
+        let mut avatar_url = None;
+        let mut repositories = RepositorySet::default();
+
+        loop {
+            let variables = org_overview::Variables {
+                login: org.to_string(),
+                cursor,
+            };
+            let raw_results = self.post_graphql::<OrgOverview>(variables).await?;
+
+            let organization = raw_results
+                .data
+                .and_then(|data| data.organization)
+                .ok_or_else(|| StatsError::NotFound(format!("organization {org}")))?;
+
+            name = name.or(organization.name.clone());
+            avatar_url = avatar_url.or(Some(organization.avatar_url.clone()));
+
+            repositories.extend(
+                organization
+                    .repositories
+                    .nodes
+                    .iter()
+                    .flatten()
+                    .flatten()
+                    .map(Repository::from),
+            );
+
+            let page_info = &organization.repositories.page_info;
+            if page_info.has_next_page {
+                cursor = page_info.end_cursor.clone();
+            } else {
+                break;
+            }
+        }
+
+        let repositories = self.filter_repositories(repositories);
+        tracing::info!("Collected {} repositories of {}", repositories.len(), org);
+
+        let repo_names = repositories
+            .iter()
+            .map(|repo| repo.name_with_owner().to_string())
+            .collect::<Vec<_>>();
+        let forks = repositories.iter().map(Repository::forks).sum::<i64>();
+        let stargazers = repositories.iter().map(Repository::stargazers).sum::<i64>();
+        let languages = top_languages(&repositories);
+
+        // an organization has no calendar of its own, so every author's work counts
+        let activity = async {
+            match self.configuration.lines_changed_backend() {
+                LinesChangedBackend::Api => self
+                    .contributor_activity(&repo_names)
+                    .await
+                    .map(|activity| OrgActivity::from_contributors(&activity)),
+                LinesChangedBackend::Git => analyze_clones(
+                    &self.configuration,
+                    Some(&repo_names),
+                    GitHistory::every_author(),
+                )
+                .await
+                .map(OrgActivity::from_history),
+            }
+        };
+        let (views, activity) = tokio::join!(self.views(&repo_names), activity);
+        let allow_partial = self.configuration.allow_partial_results();
+        let activity = Metric::from_result(
+            "Lines changed, commits and contributors",
+            activity.map_err(StatsError::from),
+            allow_partial,
+        )?;
+        let contributors = activity.value().map(|activity| activity.contributors);
+        let history = activity
+            .value()
+            .map(|activity| activity.history.clone())
+            .unwrap_or_default();
+        let total_commits = activity.clone().map(|activity| activity.commits);
+        let lines_changed = activity.map(|activity| activity.lines_changed);
+
+        let stats = StatsBuilder::default()
+            .name(name.unwrap_or(org.to_string()))
+            .avatar_url(avatar_url)
+            .total_contributions(total_commits)
+            .views(Metric::from_result(
+                "Repository views",
+                views,
+                allow_partial,
+            )?)
+            .reviews(Metric::Unavailable(
+                "reviews are only counted for users".to_string(),
+            ))
+            .organization(true)
+            .contributors(contributors)
+            .lines_changed_by_repo(lines_changed)
+            .history(history)
+            .repos(repositories)
+            .forks(forks)
+            .stargazers(stargazers)
+            .languages(languages)
+            .languages_limit(self.configuration.languages_limit())
+            .contribution_calendar(vec![])
+            .build()?;
+
+        Ok(stats)
+    }
+
+    #[tracing::instrument]
+    async fn views(&self, repos: &[String]) -> Result<Vec<DailyViews>, StatsError> {
+        let mut views = vec![];
+
+        for repo in repos {
+            if let Some(cached) = self.cache.views(repo) {
+                views.extend(cached);
+                continue;
+            }
+
+            let token = self.auth.token().await?;
+            let response = self
+                .client
+                .get(format!(
+                    "{}/repos/{}/traffic/views",
+                    self.configuration.github_url(),
+                    repo
+                ))
+                .bearer_auth(token.expose_secret())
+                .send()
+                .await?;
+            let json = check_status(response, &format!("traffic of {repo}"))
+                .await?
+                .json::<ViewTraffic>()
+                .await
+                .map_err(|e| StatsError::Schema {
+                    message: format!("malformed traffic of {repo}"),
+                    source: Some(e.into()),
+                })?;
+            let daily = json
+                .views()
+                .iter()
+                .map(|view| {
+                    DailyViews::new(
+                        repo.clone(),
+                        view.timestamp().date_naive(),
+                        view.count(),
+                        view.uniques(),
+                    )
+                })
+                .collect::<Vec<_>>();
+            self.cache.insert_views(repo, daily.clone());
+            views.extend(daily);
+        }
+        Ok(views)
+    }
+
+    #[tracing::instrument]
+    async fn lines_changed(&self, repos: &[String]) -> Result<LinesChangedByRepo, StatsError> {
+        let logins = self.configuration.actor_logins();
+        let by_repo = self
+            .contributor_activity(repos)
+            .await?
+            .into_iter()
+            .map(|(repo, contributors)| {
+                // Only the configured user's own commits are attributed to them
+                let changed = contributors
+                    .iter()
+                    .filter(|activity| {
+                        // deleted accounts can't be the user's
+                        activity.author().is_some_and(|author| {
+                            logins
+                                .iter()
+                                .any(|login| login.eq_ignore_ascii_case(author.login()))
+                        })
+                    })
+                    .flat_map(|activity| activity.weeks())
+                    .fold((0, 0), |acc, week| {
+                        (acc.0 + week.added(), acc.1 + week.deleted())
+                    });
+                (repo, changed)
+            })
+            .collect::<Vec<_>>();
+
+        let res = by_repo.iter().fold((0, 0), |acc, (_, (added, deleted))| {
+            (acc.0 + added, acc.1 + deleted)
+        });
+
         tracing::info!("Total lines added: {}, deleted: {}", res.0, res.1);
 
-        Ok(res)
+        Ok(by_repo)
     }
 
     #[tracing::instrument]
-    async fn contribution_calendar(&self) -> Result<Vec<Self::CalendarWeek>> {
+    async fn reviews(&self) -> Result<i64, StatsError> {
+        let variables = pull_request_reviews::Variables {
+            login: self.configuration.github_actor().to_string(),
+        };
+
+        let response = self.post_graphql::<PullRequestReviews>(variables).await?;
+
+        let reviews = response
+            .data
+            .and_then(|data| data.user)
+            .map(|user| {
+                user.contributions_collection
+                    .total_pull_request_review_contributions
+            })
+            .unwrap_or_default();
+
+        Ok(reviews)
+    }
+
+    #[tracing::instrument]
+    async fn contribution_calendar(&self) -> Result<Vec<ContributionWeek>, StatsError> {
         let variables = contribution_calendar::Variables {
             login: self.configuration.github_actor().to_string(),
         };
 
-        let response =
-            post_graphql::<ContributionCalendar, _>(&self.client, &self.graphql_url(), variables)
-                .await?;
+        let response = self.post_graphql::<ContributionCalendar>(variables).await?;
 
         let result = response
             .data
@@ -527,8 +932,492 @@ impl GithubExt for Github {
                 data.user
                     .map(|user| user.contributions_collection.contribution_calendar.weeks)
             })
-            .unwrap_or_default();
+            .unwrap_or_default()
+            .into_iter()
+            .map(ContributionWeek::try_from)
+            .collect::<Result<_, _>>()?;
 
         Ok(result)
     }
 }
+
+fn parse_datetime(datetime: Option<&String>) -> Option<DateTime<Utc>> {
+    datetime
+        .and_then(|datetime| DateTime::parse_from_rfc3339(datetime).ok())
+        .map(|datetime| datetime.with_timezone(&Utc))
+}
+
+fn preflight_rate_limit(limit: i64, remaining: i64, reset_at: &String) -> RateLimit {
+    RateLimit::new(
+        limit,
+        remaining,
+        parse_datetime(Some(reset_at)).unwrap_or_else(Utc::now),
+    )
+}
+
+fn new_language(name: &str, size: i64, color: Option<&String>) -> Language {
+    Language::new(
+        name.to_string(),
+        size,
+        1,
+        color.cloned().unwrap_or("#000000".to_string()),
+        0.0,
+    )
+}
+
+impl TryFrom<ContributionCalendarUserContributionsCollectionContributionCalendarWeeks>
+    for ContributionWeek
+{
+    type Error = StatsError;
+
+    fn try_from(
+        week: ContributionCalendarUserContributionsCollectionContributionCalendarWeeks,
+    ) -> Result<Self, Self::Error> {
+        let days = week
+            .contribution_days
+            .into_iter()
+            .map(|day| {
+                let date = NaiveDate::parse_from_str(&day.date, "%Y-%m-%d").map_err(|e| {
+                    StatsError::Schema {
+                        message: format!("contribution calendar date `{}`", day.date),
+                        source: Some(e.into()),
+                    }
+                })?;
+                let level = match day.contribution_level {
+                    ContributionLevel::NONE => 0,
+                    ContributionLevel::FIRST_QUARTILE => 1,
+                    ContributionLevel::SECOND_QUARTILE => 2,
+                    ContributionLevel::THIRD_QUARTILE => 3,
+                    ContributionLevel::FOURTH_QUARTILE => 4,
+                    ContributionLevel::Other(_) => 0,
+                };
+                Ok(ContributionDay::new(date, day.contribution_count, level))
+            })
+            .collect::<Result<_, StatsError>>()?;
+
+        Ok(ContributionWeek::new(days))
+    }
+}
+
+impl From<&repos_overview::ReposOverviewUserRepositoriesNodes> for Repository {
+    fn from(repo: &repos_overview::ReposOverviewUserRepositoriesNodes) -> Self {
+        let languages = repo
+            .languages
+            .iter()
+            .filter_map(|languages| languages.edges.as_ref())
+            .flatten()
+            .flatten()
+            .map(|edge| new_language(&edge.node.name, edge.size, edge.node.color.as_ref()))
+            .collect();
+
+        Repository::new(
+            repo.id.clone(),
+            repo.name_with_owner.clone(),
+            repo.stargazer_count,
+            repo.fork_count,
+            repo.is_fork,
+            repo.is_archived,
+            repo.is_private,
+            repo.is_in_organization,
+            parse_datetime(repo.pushed_at.as_ref()),
+            languages,
+            RepoSource::Owned,
+        )
+    }
+}
+
+impl From<&repos_overview::ReposOverviewUserRepositoriesContributedToNodes> for Repository {
+    fn from(repo: &repos_overview::ReposOverviewUserRepositoriesContributedToNodes) -> Self {
+        let languages = repo
+            .languages
+            .iter()
+            .filter_map(|languages| languages.edges.as_ref())
+            .flatten()
+            .flatten()
+            .map(|edge| new_language(&edge.node.name, edge.size, edge.node.color.as_ref()))
+            .collect();
+
+        Repository::new(
+            repo.id.clone(),
+            repo.name_with_owner.clone(),
+            repo.stargazers.total_count,
+            repo.fork_count,
+            repo.is_fork,
+            repo.is_archived,
+            repo.is_private,
+            repo.is_in_organization,
+            parse_datetime(repo.pushed_at.as_ref()),
+            languages,
+            RepoSource::ContributedTo,
+        )
+    }
+}
+
+impl From<&org_overview::OrgOverviewOrganizationRepositoriesNodes> for Repository {
+    fn from(repo: &org_overview::OrgOverviewOrganizationRepositoriesNodes) -> Self {
+        let languages = repo
+            .languages
+            .iter()
+            .filter_map(|languages| languages.edges.as_ref())
+            .flatten()
+            .flatten()
+            .map(|edge| new_language(&edge.node.name, edge.size, edge.node.color.as_ref()))
+            .collect();
+
+        Repository::new(
+            repo.id.clone(),
+            repo.name_with_owner.clone(),
+            repo.stargazer_count,
+            repo.fork_count,
+            repo.is_fork,
+            repo.is_archived,
+            repo.is_private,
+            repo.is_in_organization,
+            parse_datetime(repo.pushed_at.as_ref()),
+            languages,
+            RepoSource::Organization,
+        )
+    }
+}
+
+/// What every author of an organization's repositories did, from either backend
+#[derive(Clone)]
+struct OrgActivity {
+    lines_changed: LinesChangedByRepo,
+    commits: i64,
+    contributors: i64,
+    history: Vec<RepoHistory>,
+}
+
+impl OrgActivity {
+    fn from_contributors(activity: &[(String, Vec<ContributorActivity>)]) -> Self {
+        let lines_changed = activity
+            .iter()
+            .map(|(repo, contributors)| {
+                let changed = contributors
+                    .iter()
+                    .flat_map(|activity| activity.weeks())
+                    .fold((0, 0), |acc, week| {
+                        (acc.0 + week.added(), acc.1 + week.deleted())
+                    });
+                (repo.clone(), changed)
+            })
+            .collect();
+        let contributors = activity.iter().flat_map(|(_, contributors)| contributors);
+
+        Self {
+            lines_changed,
+            commits: contributors.clone().map(ContributorActivity::total).sum(),
+            // the lines and commits of deleted accounts count, but they can't be told apart
+            contributors: contributors
+                .filter_map(ContributorActivity::author)
+                .map(|author| author.login().to_lowercase())
+                .collect::<HashSet<_>>()
+                .len() as i64,
+            history: vec![],
+        }
+    }
+
+    /// Contributors are told apart by their commit emails, someone committing under
+    /// two emails counts twice
+    fn from_history(history: Vec<RepoHistory>) -> Self {
+        Self {
+            lines_changed: history
+                .iter()
+                .map(|repo| (repo.name().to_string(), (repo.added(), repo.deleted())))
+                .collect(),
+            commits: history.iter().map(RepoHistory::commit_count).sum::<usize>() as i64,
+            contributors: history
+                .iter()
+                .flat_map(RepoHistory::authors)
+                .collect::<HashSet<_>>()
+                .len() as i64,
+            history,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use axum::{
+        extract::Path,
+        response::IntoResponse,
+        routing::{get, post},
+        Json, Router,
+    };
+    use secrecy::SecretString;
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::service::test_support::stand_in;
+
+    const PRIVATE_KEY: &str = include_str!("../../resources/test/github_app_test_key.pem");
+
+    /// A stand-in GitHub answering the installation token exchange and GraphQL queries,
+    /// recording every query it was sent
+    async fn github_server() -> (String, Arc<Mutex<Vec<String>>>) {
+        let queries = Arc::new(Mutex::new(vec![]));
+        let recorded = queries.clone();
+        let app = Router::new()
+            .route(
+                "/app/installations/{installation_id}/access_tokens",
+                post(|| async {
+                    Json(json!({
+                        "token": "ghs_1",
+                        "expires_at": (Utc::now() + chrono::TimeDelta::hours(1)).to_rfc3339(),
+                    }))
+                }),
+            )
+            .route(
+                "/graphql",
+                post(move |Json(body): Json<Value>| async move {
+                    let query = body["query"].as_str().unwrap_or_default().to_string();
+                    let viewer = query.contains("viewer");
+                    recorded.lock().unwrap().push(query);
+                    let rate_limit = json!({
+                        "limit": 5000,
+                        "remaining": 4999,
+                        "resetAt": "2026-10-19T12:00:00Z",
+                    });
+                    if viewer {
+                        Json(json!({ "data": {
+                            "viewer": { "login": "stats-app[bot]" },
+                            "rateLimit": rate_limit,
+                        }}))
+                    } else {
+                        Json(json!({ "data": { "rateLimit": rate_limit } }))
+                    }
+                }),
+            );
+        let url = stand_in(app).await;
+
+        (url, queries)
+    }
+
+    #[tokio::test]
+    async fn app_preflight_skips_the_identity_checks() {
+        let (url, queries) = github_server().await;
+        let configuration = Configuration::from_json(json!({
+            "github_actor": "octocat",
+            "github_url": url,
+        }));
+        let credentials = Credentials::App {
+            app_id: "1234".to_string(),
+            installation_id: "42".to_string(),
+            private_key: SecretString::from(PRIVATE_KEY),
+        };
+        let auth = Auth::new(credentials, Client::new(), url);
+        let github = Github::new(configuration, Client::new(), auth);
+
+        let report = github.preflight().await.unwrap();
+
+        assert_eq!(report.viewer_login(), None);
+        assert!(report.actor_matches_viewer());
+        assert_eq!(report.rate_limit().remaining(), 4999);
+        assert!(report.unavailable().next().is_none());
+        let queries = queries.lock().unwrap();
+        assert_eq!(queries.len(), 1);
+        assert!(!queries[0].contains("viewer"));
+    }
+
+    /// A stand-in GitHub answering contributor statistics requests with `respond`,
+    /// and a client configured with `fields` to query it
+    async fn stats_server(
+        mut fields: Value,
+        respond: fn(&str) -> axum::response::Response,
+    ) -> (Github, Arc<Mutex<Vec<String>>>) {
+        let requests = Arc::new(Mutex::new(vec![]));
+        let recorded = requests.clone();
+        let app = Router::new().route(
+            "/repos/{owner}/{name}/stats/contributors",
+            get(
+                move |Path((owner, name)): Path<(String, String)>| async move {
+                    let repo = format!("{owner}/{name}");
+                    recorded.lock().unwrap().push(repo.clone());
+                    respond(&repo)
+                },
+            ),
+        );
+        let url = stand_in(app).await;
+        fields["github_url"] = json!(url);
+        let configuration = Configuration::from_json(fields);
+        let auth = Auth::new(
+            Credentials::Token(SecretString::from("ghp_test")),
+            Client::new(),
+            url,
+        );
+
+        (Github::new(configuration, Client::new(), auth), requests)
+    }
+
+    #[tokio::test]
+    async fn rate_limited_contributor_statistics_fail_with_the_reset_time() {
+        let (github, _) = stats_server(json!({ "github_actor": "jane" }), |repo| match repo {
+            "jane/limited" => (
+                axum::http::StatusCode::FORBIDDEN,
+                [
+                    ("x-ratelimit-remaining", "0"),
+                    ("x-ratelimit-reset", "1800000000"),
+                ],
+                "API rate limit exceeded",
+            )
+                .into_response(),
+            _ => Json(json!([])).into_response(),
+        })
+        .await;
+
+        let result = github
+            .contributor_activity(&["jane/fine".to_string(), "jane/limited".to_string()])
+            .await;
+
+        let Err(e) = result else {
+            panic!("expected the rate limit to fail the collection");
+        };
+        assert!(matches!(
+            StatsError::from(e),
+            StatsError::RateLimited { reset_at: Some(reset_at) } if reset_at.timestamp() == 1_800_000_000
+        ));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn statistics_still_computed_after_the_retries_fail_uncached() {
+        let (github, requests) = stats_server(json!({ "github_actor": "jane" }), |_| {
+            (axum::http::StatusCode::ACCEPTED, Json(json!({}))).into_response()
+        })
+        .await;
+        let repos = ["jane/busy".to_string()];
+
+        let Err(e) = github.lines_changed(&repos).await else {
+            panic!("expected the pending statistics to fail the metric");
+        };
+        assert!(e.to_string().contains("still computing"), "{e}");
+        assert!(github.cache.contributors("jane/busy").is_none());
+
+        // the next collection asks again instead of reading an empty list from the cache
+        let _ = github.lines_changed(&repos).await;
+        assert_eq!(requests.lock().unwrap().len(), 8);
+    }
+
+    #[tokio::test]
+    async fn failed_year_fails_the_total_contributions() {
+        let app = Router::new().route(
+            "/graphql",
+            post(|Json(body): Json<Value>| async move {
+                let query = body["query"].as_str().unwrap_or_default();
+                if query.contains("contributionYears") {
+                    return Json(json!({ "data": { "user": {
+                        "contributionsCollection": { "contributionYears": [2025, 2024] },
+                    }}}))
+                    .into_response();
+                }
+                if body["variables"]["from"]
+                    .as_str()
+                    .is_some_and(|from| from.starts_with("2024"))
+                {
+                    return axum::http::StatusCode::BAD_GATEWAY.into_response();
+                }
+                Json(json!({ "data": { "user": { "contributionsCollection": {
+                    "contributionCalendar": { "totalContributions": 7 },
+                }}}}))
+                .into_response()
+            }),
+        );
+        let url = stand_in(app).await;
+        let configuration = Configuration::from_json(json!({
+            "github_actor": "jane",
+            "github_url": url,
+        }));
+        let auth = Auth::new(
+            Credentials::Token(SecretString::from("ghp_test")),
+            Client::new(),
+            url,
+        );
+        let github = Github::new(configuration, Client::new(), auth);
+
+        let result = github.total_contributions().await;
+
+        assert!(matches!(result, Err(StatsError::Http(_))));
+        // unavailable, rather than the 7 contributions of the year that answered
+        let metric = Metric::from_result("Contributions", result, true).unwrap();
+        assert!(metric.value().is_none());
+    }
+
+    #[tokio::test]
+    async fn lines_changed_count_the_actor_and_aliases_in_any_case_but_no_ghosts() {
+        let (github, _) = stats_server(
+            json!({ "github_actor": "jane", "github_actor_aliases": " JANE-AT-WORK ," }),
+            |_| {
+                let activity = |author: Value, added| {
+                    let week = json!({ "w": 0, "a": added, "d": 1, "c": 1 });
+                    json!({ "author": author, "total": 1, "weeks": [week] })
+                };
+                Json(json!([
+                    activity(json!({ "login": "Jane" }), 10),
+                    activity(json!({ "login": "jane-at-work" }), 20),
+                    activity(json!({ "login": "john" }), 40),
+                    activity(Value::Null, 80),
+                ]))
+                .into_response()
+            },
+        )
+        .await;
+
+        let lines_changed = github
+            .lines_changed(&["jane/stats".to_string()])
+            .await
+            .unwrap();
+
+        assert_eq!(lines_changed, [("jane/stats".to_string(), (30, 2))]);
+    }
+
+    fn calendar_week(
+        days: Value,
+    ) -> ContributionCalendarUserContributionsCollectionContributionCalendarWeeks {
+        serde_json::from_value(json!({ "contributionDays": days })).unwrap()
+    }
+
+    #[test]
+    fn calendar_days_keep_their_date_count_and_level() {
+        let week = calendar_week(json!([
+            { "date": "2026-10-11", "contributionCount": 0, "contributionLevel": "NONE" },
+            { "date": "2026-10-12", "contributionCount": 1, "contributionLevel": "FIRST_QUARTILE" },
+            { "date": "2026-10-13", "contributionCount": 3, "contributionLevel": "SECOND_QUARTILE" },
+            { "date": "2026-10-14", "contributionCount": 5, "contributionLevel": "THIRD_QUARTILE" },
+            { "date": "2026-10-15", "contributionCount": 9, "contributionLevel": "FOURTH_QUARTILE" },
+            { "date": "2026-10-16", "contributionCount": 2, "contributionLevel": "SOMETHING_NEW" },
+        ]));
+
+        let week = ContributionWeek::try_from(week).unwrap();
+
+        let date = |day| NaiveDate::from_ymd_opt(2026, 10, day).unwrap();
+        assert_eq!(
+            week.days(),
+            [
+                ContributionDay::new(date(11), 0, 0),
+                ContributionDay::new(date(12), 1, 1),
+                ContributionDay::new(date(13), 3, 2),
+                ContributionDay::new(date(14), 5, 3),
+                ContributionDay::new(date(15), 9, 4),
+                // levels GitHub may add later show as empty rather than failing the calendar
+                ContributionDay::new(date(16), 2, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn calendar_day_with_a_malformed_date_is_a_schema_error() {
+        let week = calendar_week(json!([
+            { "date": "2026-10-11", "contributionCount": 0, "contributionLevel": "NONE" },
+            { "date": "10/12/2026", "contributionCount": 1, "contributionLevel": "FIRST_QUARTILE" },
+        ]));
+
+        let result = ContributionWeek::try_from(week);
+
+        assert!(
+            matches!(&result, Err(StatsError::Schema { message, .. }) if message.contains("10/12/2026")),
+            "{result:?}"
+        );
+    }
+}
@@ -0,0 +1,498 @@
+use std::collections::{BTreeMap, HashMap};
+
+use anyhow::Result;
+use chrono::{TimeDelta, Utc};
+use reqwest::Client;
+use secrecy::ExposeSecret;
+use serde::de::DeserializeOwned;
+
+use crate::{
+    algebra::Forge,
+    domain::{
+        calendar_from_counts, top_languages, GitlabCommit, GitlabEvent, GitlabProject, GitlabUser,
+        Language, Metric, RepoSource, Repository, RepositorySet, Stats, StatsBuilder, StatsError,
+    },
+};
+
+use super::{check_status, Auth, Configuration};
+
+/// Collects statistics from the GitLab REST API (v4)
+#[derive(Debug)]
+pub struct Gitlab {
+    configuration: Configuration,
+    client: Client,
+    auth: Auth,
+}
+
+impl Gitlab {
+    pub fn new(configuration: Configuration, client: Client, auth: Auth) -> Self {
+        Self {
+            configuration,
+            client,
+            auth,
+        }
+    }
+
+    pub fn api_url(&self) -> String {
+        format!(
+            "{}/api/v4",
+            self.configuration.gitlab_url().trim_end_matches('/')
+        )
+    }
+
+    async fn get<T: DeserializeOwned>(&self, path: &str, query: &[(&str, String)]) -> Result<T> {
+        let token = self.auth.token().await?;
+        let response = self
+            .client
+            .get(format!("{}{}", self.api_url(), path))
+            .bearer_auth(token.expose_secret())
+            .query(query)
+            .send()
+            .await?;
+        let response = check_status(response, &format!("GitLab {path}")).await?;
+
+        Ok(response.json::<T>().await.map_err(|e| malformed(path, e))?)
+    }
+
+    /// Follows GitLab's `x-next-page` header until every page is fetched
+    async fn get_paginated<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> Result<Vec<T>> {
+        let token = self.auth.token().await?;
+        let mut items = vec![];
+        let mut page = Some("1".to_string());
+
+        while let Some(current) = page {
+            let response = self
+                .client
+                .get(format!("{}{}", self.api_url(), path))
+                .bearer_auth(token.expose_secret())
+                .query(query)
+                .query(&[("per_page", "100"), ("page", current.as_str())])
+                .send()
+                .await?;
+            let response = check_status(response, &format!("GitLab {path}")).await?;
+
+            page = response
+                .headers()
+                .get("x-next-page")
+                .and_then(|next| next.to_str().ok())
+                .filter(|next| !next.is_empty())
+                .map(str::to_string);
+            items.extend(
+                response
+                    .json::<Vec<T>>()
+                    .await
+                    .map_err(|e| malformed(path, e))?,
+            );
+        }
+
+        Ok(items)
+    }
+
+    #[tracing::instrument]
+    pub async fn user(&self) -> Result<GitlabUser> {
+        self.get("/user", &[]).await
+    }
+
+    #[tracing::instrument]
+    pub async fn projects(&self, user: &GitlabUser) -> Result<Vec<Repository>> {
+        let mut projects = RepositorySet::default();
+        let owned = self
+            .get_paginated::<GitlabProject>(&format!("/users/{}/projects", user.id()), &[])
+            .await?;
+        projects.extend(
+            owned
+                .iter()
+                .map(|project| self.repository(project, RepoSource::Owned)),
+        );
+
+        if self.configuration.include_contributed_repos() {
+            let contributed = self
+                .get_paginated::<GitlabProject>(
+                    &format!("/users/{}/contributed_projects", user.id()),
+                    &[],
+                )
+                .await?;
+            projects.extend(
+                contributed
+                    .iter()
+                    .map(|project| self.repository(project, RepoSource::ContributedTo)),
+            );
+        }
+
+        let filter = self.configuration.repo_filter();
+        let mut repositories = projects
+            .into_iter()
+            .filter(|repo| filter.matches(repo))
+            .collect::<Vec<_>>();
+
+        for repo in repositories.iter_mut() {
+            let languages = self.languages(repo.id()).await.unwrap_or_else(|e| {
+                tracing::warn!(
+                    "Failed to fetch languages of {}: {:?}",
+                    repo.name_with_owner(),
+                    e
+                );
+                vec![]
+            });
+            repo.set_languages(languages);
+        }
+
+        Ok(repositories)
+    }
+
+    /// GitLab only reports the share of each language, not its size in bytes
+    async fn languages(&self, project_id: &str) -> Result<Vec<Language>> {
+        let languages = self
+            .get::<HashMap<String, f64>>(&format!("/projects/{project_id}/languages"), &[])
+            .await?;
+
+        Ok(languages
+            .into_iter()
+            .map(|(name, percentage)| {
+                let color = Language::fallback_color(&name);
+                Language::share(name, percentage, color)
+            })
+            .collect())
+    }
+
+    /// Daily counts of the user's contribution events over the past year
+    #[tracing::instrument]
+    pub async fn contribution_counts(
+        &self,
+        user: &GitlabUser,
+    ) -> Result<BTreeMap<chrono::NaiveDate, i64>> {
+        let after = (Utc::now() - TimeDelta::days(366)).date_naive();
+        let events = self
+            .get_paginated::<GitlabEvent>(
+                &format!("/users/{}/events", user.id()),
+                &[("after", after.format("%Y-%m-%d").to_string())],
+            )
+            .await?;
+
+        Ok(events.iter().fold(BTreeMap::new(), |mut acc, event| {
+            *acc.entry(event.date()).or_insert(0) += 1;
+            acc
+        }))
+    }
+
+    /// Lines added and deleted by the user's commits on each project's default branch
+    #[tracing::instrument(skip(repos))]
+    pub async fn lines_changed(
+        &self,
+        user: &GitlabUser,
+        repos: &[Repository],
+    ) -> Result<Vec<(String, (i64, i64))>> {
+        let emails = user
+            .emails()
+            .into_iter()
+            .chain(self.configuration.author_emails())
+            .map(str::to_lowercase)
+            .collect::<Vec<_>>();
+
+        let mut by_repo = vec![];
+        for repo in repos {
+            let commits = self
+                .get_paginated::<GitlabCommit>(
+                    &format!("/projects/{}/repository/commits", repo.id()),
+                    &[("with_stats", "true".to_string())],
+                )
+                .await;
+            let commits = match commits {
+                Ok(commits) => commits,
+                Err(e) => {
+                    tracing::error!(
+                        "Failed to fetch commits of {}: {:?}",
+                        repo.name_with_owner(),
+                        e
+                    );
+                    continue;
+                }
+            };
+
+            let changed = commits
+                .iter()
+                .filter(|commit| !commit.is_merge())
+                .filter(|commit| emails.contains(&commit.author_email().to_lowercase()))
+                .fold((0, 0), |acc, commit| {
+                    (acc.0 + commit.additions(), acc.1 + commit.deletions())
+                });
+            by_repo.push((repo.name_with_owner().to_string(), changed));
+        }
+
+        Ok(by_repo)
+    }
+
+    fn repository(&self, project: &GitlabProject, source: RepoSource) -> Repository {
+        Repository::new(
+            project.id().to_string(),
+            project.path_with_namespace().to_string(),
+            project.star_count(),
+            project.forks_count(),
+            project.is_fork(),
+            project.archived(),
+            project.is_private(),
+            project.is_in_group(),
+            project.last_activity_at(),
+            vec![],
+            source,
+        )
+    }
+}
+
+impl Forge for Gitlab {
+    fn provider(&self) -> &'static str {
+        "gitlab"
+    }
+
+    #[tracing::instrument]
+    async fn collect_stats(&self) -> Result<Stats> {
+        let user = self.user().await?;
+        let repositories = self.projects(&user).await?;
+        tracing::info!("Collected {} GitLab projects", repositories.len());
+
+        let forks = repositories.iter().map(Repository::forks).sum::<i64>();
+        let stargazers = repositories.iter().map(Repository::stargazers).sum::<i64>();
+        let languages = top_languages(&repositories);
+
+        let (counts, lines_changed) = tokio::join!(
+            self.contribution_counts(&user),
+            self.lines_changed(&user, &repositories)
+        );
+        let allow_partial = self.configuration.allow_partial_results();
+        let counts = Metric::from_result("Contributions", counts, allow_partial)?;
+        let today = Utc::now().date_naive();
+
+        let stats = StatsBuilder::default()
+            .name(user.name().to_string())
+            .avatar_url(user.avatar_url().map(str::to_string))
+            .total_contributions(counts.clone().map(|counts| counts.values().sum::<i64>()))
+            .views(Metric::Unavailable(
+                "GitLab doesn't report repository views".to_string(),
+            ))
+            .reviews(Metric::Unavailable(
+                "GitLab doesn't report reviews".to_string(),
+            ))
+            .lines_changed_by_repo(Metric::from_result(
+                "Lines changed",
+                lines_changed,
+                allow_partial,
+            )?)
+            .repos(repositories)
+            .forks(forks)
+            .stargazers(stargazers)
+            .languages(languages)
+            .languages_limit(self.configuration.languages_limit())
+            .contribution_calendar(counts.map(|counts| calendar_from_counts(&counts, today)))
+            .build()?;
+
+        Ok(stats)
+    }
+}
+
+fn malformed(path: &str, error: reqwest::Error) -> StatsError {
+    StatsError::Schema {
+        message: format!("malformed GitLab response from {path}"),
+        source: Some(error.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        extract::{Path, Query},
+        response::IntoResponse,
+        routing::get,
+        Json, Router,
+    };
+    use secrecy::SecretString;
+    use serde_json::json;
+
+    use super::*;
+    use crate::{
+        domain::SizeUnit,
+        service::{test_support::stand_in, Credentials},
+    };
+
+    fn project(id: i64, path: &str, stars: i64) -> serde_json::Value {
+        json!({
+            "id": id,
+            "path_with_namespace": path,
+            "star_count": stars,
+            "forks_count": 1,
+            "visibility": "public",
+            "namespace": { "kind": "user" },
+            "forked_from_project": null,
+            "last_activity_at": "2026-10-01T00:00:00Z",
+        })
+    }
+
+    /// A stand-in GitLab serving one user with two projects, split over two pages
+    async fn gitlab_server() -> String {
+        let app = Router::new()
+            .route(
+                "/api/v4/user",
+                get(|| async {
+                    Json(json!({
+                        "id": 7,
+                        "username": "jane",
+                        "name": "Jane Doe",
+                        "avatar_url": null,
+                        "email": "jane@example.com",
+                        "commit_email": null,
+                    }))
+                }),
+            )
+            .route(
+                "/api/v4/users/7/projects",
+                get(|Query(query): Query<HashMap<String, String>>| async move {
+                    match query.get("page").map(String::as_str) {
+                        Some("1") => (
+                            [("x-next-page", "2")],
+                            Json(json!([project(1, "jane/app", 3)])),
+                        )
+                            .into_response(),
+                        _ => (
+                            [("x-next-page", "")],
+                            Json(json!([project(2, "jane/lib", 4)])),
+                        )
+                            .into_response(),
+                    }
+                }),
+            )
+            .route(
+                "/api/v4/projects/{id}/languages",
+                get(|Path(id): Path<i64>| async move {
+                    match id {
+                        1 => Json(json!({ "Rust": 75.5, "Shell": 24.5 })),
+                        _ => Json(json!({ "Rust": 100.0 })),
+                    }
+                }),
+            )
+            .route(
+                "/api/v4/users/7/events",
+                get(|| async {
+                    let today = Utc::now().to_rfc3339();
+                    Json(json!([{ "created_at": today }, { "created_at": today }]))
+                }),
+            )
+            .route(
+                "/api/v4/projects/{id}/repository/commits",
+                get(|| async {
+                    Json(json!([
+                        {
+                            "author_email": "Jane@Example.com",
+                            "parent_ids": ["a"],
+                            "stats": { "additions": 10, "deletions": 2 },
+                        },
+                        {
+                            "author_email": "jane@example.com",
+                            "parent_ids": ["a", "b"],
+                            "stats": { "additions": 100, "deletions": 100 },
+                        },
+                        {
+                            "author_email": "bob@example.com",
+                            "parent_ids": ["a"],
+                            "stats": { "additions": 5, "deletions": 5 },
+                        },
+                    ]))
+                }),
+            );
+        let url = stand_in(app).await;
+
+        url
+    }
+
+    async fn gitlab() -> Gitlab {
+        let url = gitlab_server().await;
+        let configuration = Configuration::from_json(json!({
+            "github_actor": "jane",
+            "gitlab_url": url,
+        }));
+        let auth = Auth::new(
+            Credentials::Token(SecretString::from("glpat-token")),
+            Client::new(),
+            url,
+        );
+
+        Gitlab::new(configuration, Client::new(), auth)
+    }
+
+    #[tokio::test]
+    async fn projects_follow_the_next_page_header() {
+        let gitlab = gitlab().await;
+        let user = gitlab.user().await.unwrap();
+
+        let projects = gitlab.projects(&user).await.unwrap();
+
+        let names = projects
+            .iter()
+            .map(Repository::name_with_owner)
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["jane/app", "jane/lib"]);
+    }
+
+    #[tokio::test]
+    async fn languages_are_shares_and_not_bytes() {
+        let gitlab = gitlab().await;
+
+        let languages = gitlab.languages("1").await.unwrap();
+
+        let rust = languages.iter().find(|l| l.name() == "Rust").unwrap();
+        assert_eq!(rust.size_unit(), SizeUnit::BasisPoints);
+        assert_eq!(rust.size(), 7550);
+        assert_eq!(rust.bytes(), None);
+    }
+
+    #[tokio::test]
+    async fn collects_the_stats_of_the_user() {
+        let stats = gitlab().await.collect_stats().await.unwrap();
+
+        assert_eq!(stats.name(), "Jane Doe");
+        assert_eq!(stats.stargazers(), 7);
+        assert_eq!(stats.forks(), 2);
+        assert_eq!(stats.total_contributions(), Some(2));
+        // merges and other authors are left out, in both projects
+        assert_eq!(stats.lines_changed(), Some((20, 4)));
+        assert_eq!(stats.views(), None);
+        assert_eq!(stats.reviews(), None);
+
+        let (name, rust) = &stats.languages()[0];
+        assert_eq!(name, "Rust");
+        assert_eq!(rust.bytes(), None);
+        assert!((rust.proportion() - 87.75).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn rejected_token_is_an_auth_error() {
+        let app = Router::new().route(
+            "/api/v4/user",
+            get(|| async { axum::http::StatusCode::UNAUTHORIZED }),
+        );
+        let url = stand_in(app).await;
+        let configuration = Configuration::from_json(json!({
+            "github_actor": "jane",
+            "gitlab_url": url,
+        }));
+        let auth = Auth::new(
+            Credentials::Token(SecretString::from("expired")),
+            Client::new(),
+            url,
+        );
+
+        let Err(error) = Gitlab::new(configuration, Client::new(), auth)
+            .collect_stats()
+            .await
+        else {
+            panic!("expected the rejected token to fail the collection");
+        };
+
+        assert!(matches!(
+            error.downcast_ref::<StatsError>(),
+            Some(StatsError::Auth { .. })
+        ));
+    }
+}
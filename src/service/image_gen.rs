@@ -1,103 +1,163 @@
-use std::{collections::HashMap, fs};
+use std::{cmp::Reverse, collections::HashMap, fs, io};
 
-use chrono::NaiveDate;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::{Datelike, Utc};
+use serde::Deserialize;
 
-use crate::{algebra::ImageGenExt, domain::Stats};
+use crate::{
+    algebra::ImageGenExt,
+    domain::{CustomCard, Stats, StatsError, StatsMetric},
+};
+
+use super::{Font, LanguagesChart};
+
+/// Shown in place of a metric that couldn't be collected
+const UNAVAILABLE: &str = "n/a";
+
+/// Background of a badge whose metric couldn't be collected
+const UNAVAILABLE_COLOR: &str = "#9f9f9f";
+
+/// Color scheme of a rendered card
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Theme {
+    #[default]
+    Light,
+    Dark,
+}
 
 pub struct ImageGen {
     template_folder: String,
     output_folder: String,
+    languages_chart: LanguagesChart,
 }
 
 impl ImageGenExt for ImageGen {
-    fn generate_overview(&self, stats: &Stats) -> Result<(), anyhow::Error> {
-        let svg_content = fs::read_to_string(format!("{}/overview.svg", self.template_folder))?;
+    fn render_overview(&self, stats: &Stats) -> Result<String, StatsError> {
+        // where overview.svg puts the title, and the table below it
+        const LEFT: f64 = 28.0;
+        const RIGHT: f64 = 339.0;
+        const LABEL_X: f64 = 51.0;
+        const TITLE_SUFFIX: &str = "'s GitHub Statistics";
+
+        let svg_content = self.read_template("overview.svg")?;
         let mut tags_map = HashMap::new();
 
-        tags_map.insert("name".to_string(), stats.name().to_string());
-        tags_map.insert("stars".to_string(), stats.stargazers().to_string());
-        tags_map.insert("forks".to_string(), stats.forks().to_string());
-        tags_map.insert(
-            "contributions".to_string(),
-            stats.total_contributions().to_string(),
+        let (avatar, title_x) = match stats.avatar_url() {
+            Some(url) => (
+                format!(
+                    r#"<image x="{LEFT}" y="25" width="20" height="20" href="{url}" xlink:href="{url}" clip-path="url(#avatar-clip)" />"#,
+                    url = escape_html(url)
+                ),
+                LEFT + 27.0,
+            ),
+            None => (String::new(), LEFT),
+        };
+        let name = Font::HelveticaBold.truncate(
+            stats.name(),
+            14.0,
+            RIGHT - title_x - Font::HelveticaBold.text_width(TITLE_SUFFIX, 14.0),
         );
-        let (added, removed) = stats.lines_changed();
-        tags_map.insert("lines_changed".to_string(), format!("{}", added + removed));
-        tags_map.insert("views".to_string(), stats.views().to_string());
-        tags_map.insert("repos".to_string(), stats.repos().iter().len().to_string());
+        tags_map.insert("name".to_string(), escape_html(&name));
+        tags_map.insert("avatar".to_string(), avatar);
+        tags_map.insert("title_x".to_string(), title_x.to_string());
 
-        fs::create_dir_all(&self.output_folder)?;
-        let modified_content = Self::replace_tags(svg_content, &tags_map)?;
+        let contributions_label = if stats.is_organization() {
+            "Commits by all contributors"
+        } else {
+            "All-time contributions"
+        };
+        let mut rows = vec![
+            ("star", "Stars", stats.stargazers().to_string()),
+            ("repo-forked", "Forks", stats.forks().to_string()),
+            (
+                "repo-push",
+                contributions_label,
+                or_unavailable(stats.total_contributions()),
+            ),
+            (
+                "diff",
+                "Lines of code changed",
+                or_unavailable(
+                    stats
+                        .lines_changed()
+                        .map(|(added, removed)| added + removed),
+                ),
+            ),
+            (
+                "eye",
+                "Repository views (past two weeks)",
+                or_unavailable(stats.views()),
+            ),
+            (
+                "repo",
+                "Repositories with contributions",
+                stats.repos().len().to_string(),
+            ),
+        ];
+        if let Some(contributors) = stats.contributors() {
+            rows.push(("people", "Contributors", contributors.to_string()));
+        }
+        // the card grows by a row's height past the six rows every account has
+        let height = 66 + 24 * rows.len();
+        tags_map.insert("height".to_string(), height.to_string());
+        tags_map.insert("background_height".to_string(), (height - 10).to_string());
 
-        fs::write(
-            format!("{}/overview.svg", self.output_folder),
-            modified_content,
-        )?;
+        // the values line up after the widest label
+        let label_width = rows
+            .iter()
+            .map(|(_, label, _)| Font::Helvetica.text_width(label, 12.0))
+            .fold(0.0, f64::max);
+        let value_x = (LABEL_X + label_width + 16.0).round();
+        let rows = rows
+            .iter()
+            .enumerate()
+            .map(|(idx, (icon, label, value))| {
+                let baseline = 68 + 24 * idx;
+                format!(
+                    r##"<g class="row" style="animation-delay: {delay}ms">
+<use xlink:href="#{icon}" x="{LEFT}" y="{icon_y}" width="16" height="16" class="octicon" />
+<text class="label" x="{LABEL_X}" y="{baseline}">{label}</text>
+<text class="value" x="{value_x}" y="{baseline}">{value}</text>
+</g>"##,
+                    delay = 150 * idx,
+                    icon_y = baseline - 12,
+                    value = escape_html(&Font::Helvetica.truncate(value, 12.0, RIGHT - value_x)),
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        tags_map.insert("rows".to_string(), rows);
 
-        Ok(())
+        Self::replace_tags(svg_content, &tags_map)
     }
 
-    fn generate_languages(&self, stats: &Stats) -> Result<(), anyhow::Error> {
-        let svg_content = fs::read_to_string(format!("{}/languages.svg", self.template_folder))?;
-        let mut progress = "".to_string();
-        let mut lang_list = "".to_string();
-        let mut tags_map = HashMap::new();
-
-        for (idx, (language, data)) in stats.languages().iter().enumerate() {
-            let proportion = format!("{:.2}", data.proportion());
-            let progress_tmp = format!(
-                r#"<span style="background-color: {}; width: {}%;" class="progress-item"></span>"#,
-                data.color(),
-                proportion
-            );
-
-            let lang_list_tmp = format!(
-                r#"<li style="animation-delay: {}ms;">
-<svg xmlns="http://www.w3.org/2000/svg" class="octicon" style="fill:{};"
-viewBox="0 0 16 16" version="1.1" width="16" height="16"><path
-fill-rule="evenodd" d="M8 4a4 4 0 100 8 4 4 0 000-8z"></path></svg>
-<span class="lang">{}</span>
-<span class="percent">{}%</span>
-</li>
-
-            "#,
-                150 * idx,
-                data.color(),
-                language,
-                proportion
-            );
-            progress.push_str(&progress_tmp);
-            lang_list.push_str(&lang_list_tmp);
+    fn render_languages(&self, stats: &Stats, limit: Option<usize>) -> Result<String, StatsError> {
+        match self.languages_chart {
+            LanguagesChart::Bar => self.render_languages_bar(stats, limit),
+            LanguagesChart::Donut => self.render_languages_donut(stats, limit, 1),
+            LanguagesChart::DonutWide => self.render_languages_donut(stats, limit, 2),
         }
-
-        tags_map.insert("progress".to_string(), progress);
-        tags_map.insert("lang_list".to_string(), lang_list);
-
-        fs::create_dir_all(&self.output_folder)?;
-        let modified_content = Self::replace_tags(svg_content, &tags_map)?;
-
-        fs::write(
-            format!("{}/languages.svg", self.output_folder),
-            modified_content,
-        )?;
-
-        Ok(())
     }
 
-    fn generate_contributions_grid(&self, stats: &Stats) -> Result<(), anyhow::Error> {
-        let svg_content =
-            fs::read_to_string(format!("{}/contribution_grid.svg", self.template_folder))?;
+    fn render_contributions_grid(&self, stats: &Stats) -> Result<String, StatsError> {
+        let svg_content = self.read_template("contribution_grid.svg")?;
         let mut grid: String = "".to_string();
         let mut months: Vec<(String, usize)> = Vec::new();
 
         let mut animation_delay = 0;
 
-        for (week_index, week) in stats.contribution_calendar().iter().enumerate() {
-            if let Some(first_day) = week.contribution_days.last() {
-                let date = first_day.date.to_string();
-                let naive_date = NaiveDate::parse_from_str(&date, " %Y-%m-%d")?;
+        let calendar = stats.contribution_calendar();
+        if calendar.is_none() {
+            grid.push_str(&format!(
+                r#"<text x="40" y="70" class="day-label">{UNAVAILABLE}</text>"#
+            ));
+        }
+
+        for (week_index, week) in calendar.unwrap_or_default().iter().enumerate() {
+            if let Some(last_day) = week.days().last() {
                 // Get the first 3 letters of the month
-                let month = naive_date.format("%b").to_string();
+                let month = last_day.date().format("%b").to_string();
                 if months
                     .last()
                     .is_none_or(|(last_month, _)| *last_month != month)
@@ -106,17 +166,18 @@ fill-rule="evenodd" d="M8 4a4 4 0 100 8 4 4 0 000-8z"></path></svg>
                 }
             }
 
-            grid.push_str("<div>");
-            for day in week.contribution_days.iter() {
-                let color = day.color.clone();
+            for day in week.days() {
+                let color = day.color();
+                // a column per week and a row per weekday, so a week starting mid-week keeps its days in place
+                let x = 40 + week_index * 12;
+                let y = 50 + day.date().weekday().num_days_from_sunday() * 12;
 
                 grid.push_str(&format!(
-                    r#"<div class="contribution_cell" style="background-color: {color}; animation-delay: {animation_delay}ms;"></div>"#,
+                    r#"<rect x="{x}" y="{y}" width="10" height="10" rx="2" ry="2" fill="{color}" class="contribution_cell" style="animation-delay: {animation_delay}ms" />"#,
                 ));
 
                 animation_delay += 10; // Increment delay for the next cell
             }
-            grid.push_str("</div>");
         }
 
         let month_labels = months
@@ -129,21 +190,389 @@ fill-rule="evenodd" d="M8 4a4 4 0 100 8 4 4 0 000-8z"></path></svg>
             (month_labels, animation_delay + 150)
         }).0;
 
-        let modified_content = Self::replace_tags(
+        Self::replace_tags(
             svg_content,
             &HashMap::from([
                 ("grid".to_string(), grid),
                 ("months".to_string(), month_labels),
             ]),
-        )?;
+        )
+    }
 
-        fs::write(
-            format!("{}/contribution_grid.svg", self.output_folder),
-            modified_content,
-        )?;
+    fn render_dashboard(&self, stats: &Stats) -> Result<String, StatsError> {
+        let html_content = self.read_template("dashboard.html")?;
+
+        // embedded as images, the cards' styles and ids would clash with the page and each other
+        let cards = [
+            ("GitHub statistics overview", self.render_overview(stats)?),
+            ("Most used languages", self.render_languages(stats, None)?),
+            (
+                "Contribution calendar",
+                self.render_contributions_grid(stats)?,
+            ),
+        ]
+        .into_iter()
+        .map(|(alt, svg)| {
+            let dark = Self::apply_theme(svg.clone(), Theme::Dark);
+            format!(
+                r#"<picture>
+<source media="(prefers-color-scheme: dark)" srcset="{}">
+<img alt="{alt}" src="{}">
+</picture>"#,
+                svg_data_uri(&dark),
+                svg_data_uri(&svg)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+        let lines_changed = stats.lines_changed_by_repo();
+        let mut repos = stats.repos().iter().collect::<Vec<_>>();
+        repos.sort_by_key(|repo| Reverse(repo.stargazers()));
+
+        let mut repo_rows = "".to_string();
+        for repo in repos {
+            let changed = lines_changed.map(|by_repo| {
+                by_repo
+                    .iter()
+                    .find(|(name, _)| name.eq_ignore_ascii_case(repo.name_with_owner()))
+                    .map(|(_, changed)| *changed)
+                    .unwrap_or_default()
+            });
+            let language = repo
+                .languages()
+                .iter()
+                .max_by_key(|language| language.size())
+                .map(|language| escape_html(language.name()))
+                .unwrap_or_default();
+            let pushed_at = repo
+                .pushed_at()
+                .map(|pushed_at| pushed_at.format("%Y-%m-%d").to_string())
+                .unwrap_or_default();
+
+            repo_rows.push_str(&format!(
+                "<tr><td>{}</td>{}{}{}{}<td>{}</td><td>{}</td></tr>\n",
+                escape_html(repo.name_with_owner()),
+                number_cell(Some(repo.stargazers())),
+                number_cell(Some(repo.forks())),
+                number_cell(changed.map(|(added, _)| added)),
+                number_cell(changed.map(|(_, deleted)| deleted)),
+                language,
+                pushed_at
+            ));
+        }
+
+        let mut language_rows = "".to_string();
+        for (language, data) in stats.languages() {
+            language_rows.push_str(&format!(
+                r#"<tr><td><span class="swatch" style="background-color: {color};"></span>{}</td>{}<td class="number" data-value="{proportion}">{proportion:.2}%</td><td><div class="bar"><span style="background-color: {color}; width: {proportion:.2}%;"></span></div></td></tr>
+"#,
+                escape_html(language),
+                number_cell(data.bytes()),
+                color = data.color(),
+                proportion = data.proportion(),
+            ));
+        }
+
+        let heatmap = match stats.contribution_calendar() {
+            Some(calendar) => {
+                let mut heatmap = r#"<div class="heatmap">"#.to_string();
+                // the first week may start mid-week, pad it so days stay in their weekday row
+                let padding = calendar
+                    .first()
+                    .and_then(|week| week.days().first())
+                    .map(|day| day.date().weekday().num_days_from_sunday())
+                    .unwrap_or_default();
+                for _ in 0..padding {
+                    heatmap.push_str(r#"<span class="empty"></span>"#);
+                }
+                for day in calendar.iter().flat_map(|week| week.days()) {
+                    let contributions = match day.count() {
+                        0 => "No contributions".to_string(),
+                        1 => "1 contribution".to_string(),
+                        count => format!("{count} contributions"),
+                    };
+                    heatmap.push_str(&format!(
+                        r#"<span style="background-color: {};" title="{} on {}"></span>"#,
+                        day.color(),
+                        contributions,
+                        day.date().format("%B %-d, %Y")
+                    ));
+                }
+                heatmap.push_str("</div>");
+                heatmap
+            }
+            None => format!(r#"<p class="muted">{UNAVAILABLE}</p>"#),
+        };
+
+        let unavailable = stats
+            .unavailable()
+            .iter()
+            .map(|(metric, _)| *metric)
+            .collect::<Vec<_>>();
+        let unavailable = if unavailable.is_empty() {
+            "".to_string()
+        } else {
+            format!(" · unavailable: {}", unavailable.join(", "))
+        };
+
+        Self::replace_tags(
+            html_content,
+            &HashMap::from([
+                ("name".to_string(), escape_html(stats.name())),
+                (
+                    "generated_at".to_string(),
+                    Utc::now().format("%Y-%m-%d %H:%M UTC").to_string(),
+                ),
+                ("unavailable".to_string(), unavailable),
+                ("cards".to_string(), cards),
+                ("repo_rows".to_string(), repo_rows),
+                ("language_rows".to_string(), language_rows),
+                ("heatmap".to_string(), heatmap),
+            ]),
+        )
+    }
+
+    fn render_badge(&self, stats: &Stats, metric: StatsMetric) -> Result<String, StatsError> {
+        let svg_content = self.read_template("badge.svg")?;
+
+        let label = metric.label();
+        let (value, color) = match metric.value(stats) {
+            Some(value) => (value, metric.color(stats)),
+            None => (UNAVAILABLE.to_string(), UNAVAILABLE_COLOR.to_string()),
+        };
+
+        // widths as shields.io computes them: the text truncated to an odd number of
+        // pixels so it centers on the pixel grid, 5px of padding on either side
+        let text_length = |text: &str| {
+            let width = Font::Verdana.text_width(text, 11.0) as u32;
+            width + (1 - width % 2)
+        };
+        let label_length = text_length(label);
+        let value_length = text_length(&value);
+        let label_width = label_length + 10;
+        let value_width = value_length + 10;
+
+        Self::replace_tags(
+            svg_content,
+            &HashMap::from([
+                ("label".to_string(), escape_html(label)),
+                ("value".to_string(), escape_html(&value)),
+                ("color".to_string(), color),
+                ("width".to_string(), (label_width + value_width).to_string()),
+                ("label_width".to_string(), label_width.to_string()),
+                ("value_width".to_string(), value_width.to_string()),
+                // text is drawn at a tenth of the scale, positions are its centers
+                ("label_x".to_string(), (5 * label_width + 10).to_string()),
+                (
+                    "value_x".to_string(),
+                    (10 * label_width + 5 * value_width - 10).to_string(),
+                ),
+                ("label_length".to_string(), (10 * label_length).to_string()),
+                ("value_length".to_string(), (10 * value_length).to_string()),
+            ]),
+        )
+    }
+
+    fn render_custom_card(&self, stats: &Stats, card: &CustomCard) -> Result<String, StatsError> {
+        let svg_content = Self::read_file(card.template().to_string())?;
+
+        // the title sits above the first row's baseline, like on the overview card
+        const HEADER_HEIGHT: u32 = 44;
+
+        let card_rows = card.rows(stats);
+        let rows = card_rows
+            .iter()
+            .enumerate()
+            .map(|(idx, row)| {
+                Self::replace_tags(
+                    card.row().to_string(),
+                    &HashMap::from([
+                        ("label".to_string(), escape_html(row.label())),
+                        (
+                            "value".to_string(),
+                            escape_html(row.value().unwrap_or(UNAVAILABLE)),
+                        ),
+                        ("color".to_string(), escape_html(row.color())),
+                        ("index".to_string(), idx.to_string()),
+                        (
+                            "y".to_string(),
+                            (HEADER_HEIGHT + card.row_height() * (idx as u32 + 1)).to_string(),
+                        ),
+                        ("delay".to_string(), (150 * idx).to_string()),
+                    ]),
+                )
+            })
+            .collect::<Result<Vec<_>, _>>()?
+            .join("\n");
+
+        let content_height = HEADER_HEIGHT + card.row_height() * card_rows.len() as u32;
+
+        let mut tags_map = StatsMetric::ALL
+            .iter()
+            .map(|metric| {
+                (
+                    metric.name().to_string(),
+                    escape_html(&or_unavailable(metric.value(stats))),
+                )
+            })
+            .collect::<HashMap<_, _>>();
+        tags_map.insert("name".to_string(), escape_html(stats.name()));
+        tags_map.insert("rows".to_string(), rows);
+        tags_map.insert("content_height".to_string(), content_height.to_string());
+        tags_map.insert("height".to_string(), (content_height + 22).to_string());
+        tags_map.insert(
+            "background_height".to_string(),
+            (content_height + 12).to_string(),
+        );
+
+        Self::replace_tags(svg_content, &tags_map)
+    }
+
+    fn generate_overview(&self, stats: &Stats) -> Result<(), StatsError> {
+        self.write_themed("overview.svg", self.render_overview(stats)?)
+    }
+
+    fn generate_languages(&self, stats: &Stats) -> Result<(), StatsError> {
+        self.write_themed("languages.svg", self.render_languages(stats, None)?)
+    }
+
+    fn generate_contributions_grid(&self, stats: &Stats) -> Result<(), StatsError> {
+        self.write_themed(
+            "contribution_grid.svg",
+            self.render_contributions_grid(stats)?,
+        )
+    }
+
+    fn generate_dashboard(&self, stats: &Stats) -> Result<(), StatsError> {
+        self.write("index.html", self.render_dashboard(stats)?)
+    }
 
+    fn generate_badges(&self, stats: &Stats, metrics: &[StatsMetric]) -> Result<(), StatsError> {
+        for metric in metrics {
+            self.write(
+                &format!("badge_{}.svg", metric.name()),
+                self.render_badge(stats, *metric)?,
+            )?;
+        }
+        Ok(())
+    }
+
+    fn generate_custom_cards(&self, stats: &Stats, cards: &[CustomCard]) -> Result<(), StatsError> {
+        for card in cards {
+            self.write(card.output(), self.render_custom_card(stats, card)?)?;
+        }
         Ok(())
     }
+
+    fn generate_leaderboard(&self, team: &[(String, Stats)]) -> Result<(), StatsError> {
+        let svg_content = self.read_template("leaderboard.svg")?;
+
+        let lines_changed = |stats: &Stats| {
+            stats
+                .lines_changed()
+                .map(|(added, removed)| added + removed)
+        };
+
+        let mut ranking = team.iter().collect::<Vec<_>>();
+        ranking.sort_by_key(|(_, stats)| {
+            std::cmp::Reverse((
+                stats.total_contributions(),
+                lines_changed(stats),
+                stats.reviews(),
+            ))
+        });
+
+        // the member's name ends before the widest heading starts
+        const MEMBER_X: f64 = 50.0;
+        const MEMBER_WIDTH: f64 = 120.0;
+
+        let row = |idx: usize, total: bool, cells: [String; 5]| {
+            let [rank, name, contributions, lines, reviews] = cells;
+            let baseline = 68 + 24 * idx;
+            let (class, separator) = if total {
+                (
+                    "row total",
+                    format!(
+                        r#"<line x1="21" y1="{y}" x2="459" y2="{y}" />
+"#,
+                        y = baseline - 17
+                    ),
+                )
+            } else {
+                ("row", "".to_string())
+            };
+            format!(
+                r#"<g class="{class}" style="animation-delay: {delay}ms">
+{separator}<text x="26" y="{baseline}">{rank}</text>
+<text x="{MEMBER_X}" y="{baseline}">{name}</text>
+<text class="number" x="274" y="{baseline}">{contributions}</text>
+<text class="number" x="374" y="{baseline}">{lines}</text>
+<text class="number" x="454" y="{baseline}">{reviews}</text>
+</g>
+"#,
+                delay = 150 * idx,
+                name = escape_html(&Font::Helvetica.truncate(&name, 12.0, MEMBER_WIDTH)),
+            )
+        };
+
+        let mut rows = "".to_string();
+        for (idx, (login, stats)) in ranking.iter().enumerate() {
+            let name = if stats.name().is_empty() {
+                login.as_str()
+            } else {
+                stats.name()
+            };
+            rows.push_str(&row(
+                idx,
+                false,
+                [
+                    (idx + 1).to_string(),
+                    name.to_string(),
+                    or_unavailable(stats.total_contributions()),
+                    or_unavailable(lines_changed(stats)),
+                    or_unavailable(stats.reviews()),
+                ],
+            ));
+        }
+        rows.push_str(&row(
+            ranking.len(),
+            true,
+            [
+                "".to_string(),
+                "Team".to_string(),
+                or_unavailable(
+                    team.iter()
+                        .map(|(_, stats)| stats.total_contributions())
+                        .sum::<Option<i64>>(),
+                ),
+                or_unavailable(
+                    team.iter()
+                        .map(|(_, stats)| lines_changed(stats))
+                        .sum::<Option<i64>>(),
+                ),
+                or_unavailable(
+                    team.iter()
+                        .map(|(_, stats)| stats.reviews())
+                        .sum::<Option<i64>>(),
+                ),
+            ],
+        ));
+
+        // the heading, one row per member and the total row
+        let height = 66 + 24 * (team.len() + 1);
+
+        let modified_content = Self::replace_tags(
+            svg_content,
+            &HashMap::from([
+                ("rows".to_string(), rows),
+                ("height".to_string(), height.to_string()),
+                ("background_height".to_string(), (height - 10).to_string()),
+            ]),
+        )?;
+
+        self.write("leaderboard.svg", modified_content)
+    }
 }
 
 impl ImageGen {
@@ -151,13 +580,238 @@ impl ImageGen {
         Self {
             template_folder,
             output_folder,
+            languages_chart: LanguagesChart::default(),
         }
     }
 
+    pub fn with_languages_chart(mut self, languages_chart: LanguagesChart) -> Self {
+        self.languages_chart = languages_chart;
+        self
+    }
+
+    /// The languages as a progress bar above a two-column list
+    fn render_languages_bar(
+        &self,
+        stats: &Stats,
+        limit: Option<usize>,
+    ) -> Result<String, StatsError> {
+        // where languages.svg puts the progress bar, and the two columns of the list below it
+        const LEFT: f64 = 21.0;
+        const WIDTH: f64 = 318.0;
+        const PROGRESS_Y: f64 = 53.0;
+        const LIST_TOP: f64 = 77.0;
+        const LIST_BOTTOM: f64 = 193.0;
+        const COLUMN_GAP: f64 = 8.0;
+        const ROW_HEIGHT: f64 = 16.0;
+        const ROW_PITCH: f64 = 26.0;
+
+        let svg_content = self.read_template("languages.svg")?;
+        let mut progress = "".to_string();
+        let mut lang_list = "".to_string();
+        let mut tags_map = HashMap::new();
+
+        let column_width = (WIDTH - COLUMN_GAP) / 2.0;
+        // languages past the card's bottom edge are left out of the list, the bar shows them all
+        let rows_fitting = ((LIST_BOTTOM - LIST_TOP - ROW_HEIGHT) / ROW_PITCH) as usize + 1;
+
+        let limit = limit.unwrap_or(usize::MAX);
+        let mut offset = 0.0;
+        for (idx, (language, data)) in stats.languages().iter().take(limit).enumerate() {
+            let proportion = format!("{:.2}", data.proportion());
+            let progress_tmp = format!(
+                r#"<rect x="{:.2}" y="{PROGRESS_Y}" width="{:.2}" height="8" fill="{}" class="progress-item" />
+"#,
+                LEFT + WIDTH * offset / 100.0,
+                WIDTH * data.proportion() / 100.0,
+                data.color(),
+            );
+            offset += data.proportion();
+            progress.push_str(&progress_tmp);
+
+            if idx >= 2 * rows_fitting {
+                continue;
+            }
+            let x = LEFT + (idx % 2) as f64 * (column_width + COLUMN_GAP);
+            let top = LIST_TOP + (idx / 2) as f64 * ROW_PITCH;
+            lang_list.push_str(&language_item(
+                idx,
+                (x, top),
+                language,
+                data.color(),
+                &format!("{proportion}%"),
+                column_width,
+            ));
+        }
+
+        tags_map.insert("progress".to_string(), progress);
+        tags_map.insert("lang_list".to_string(), lang_list);
+
+        Self::replace_tags(svg_content, &tags_map)
+    }
+
+    /// The languages as a donut, with a legend of `columns` columns to its right.
+    /// Languages the legend has no room for are grouped into one `Other` slice.
+    fn render_languages_donut(
+        &self,
+        stats: &Stats,
+        limit: Option<usize>,
+        columns: usize,
+    ) -> Result<String, StatsError> {
+        // the donut below the title, and the legend to its right
+        const CENTER: (f64, f64) = (87.0, 123.0);
+        const OUTER_RADIUS: f64 = 66.0;
+        const INNER_RADIUS: f64 = 40.0;
+        const LEGEND_LEFT: f64 = 175.0;
+        const LEGEND_TOP: f64 = 55.0;
+        const LEGEND_ROWS: usize = 7;
+        const COLUMN_WIDTH: f64 = 164.0;
+        const COLUMN_GAP: f64 = 8.0;
+        const ROW_PITCH: f64 = 20.0;
+        const OTHER_COLOR: &str = "#8b949e";
+        // shares below this round to 0.00%
+        const NEGLIGIBLE: f64 = 0.005;
+
+        let svg_content = self.read_template("languages_donut.svg")?;
+        let mut tags_map = HashMap::new();
+
+        let capacity = columns * LEGEND_ROWS;
+        let mut slices = stats
+            .languages()
+            .iter()
+            .take(limit.unwrap_or(usize::MAX))
+            .map(|(language, data)| (language.as_str(), data.proportion(), data.color()))
+            .collect::<Vec<_>>();
+        let rest = |slices: &[(&str, f64, &str)]| {
+            100.0 - slices.iter().map(|(_, share, _)| share).sum::<f64>()
+        };
+        if slices.len() > capacity || (slices.len() == capacity && rest(&slices) >= NEGLIGIBLE) {
+            slices.truncate(capacity - 1);
+        }
+        let other = rest(&slices);
+        if !slices.is_empty() && other >= NEGLIGIBLE {
+            slices.push(("Other", other, OTHER_COLOR));
+        }
+
+        let mid_radius = (OUTER_RADIUS + INNER_RADIUS) / 2.0;
+        // drawn under the slices, it is all that shows without any language
+        let mut chart = format!(
+            r#"<circle class="track" cx="{}" cy="{}" r="{mid_radius}" stroke-width="{}" />
+"#,
+            CENTER.0,
+            CENTER.1,
+            OUTER_RADIUS - INNER_RADIUS,
+        );
+        let mut legend = "".to_string();
+        let mut start = 0.0;
+        for (idx, (language, share, color)) in slices.iter().enumerate() {
+            let percent = format!("{share:.2}%");
+            let end = start + share / 100.0;
+            // an arc can't start and end on the same point, a whole ring is a stroked circle
+            let slice = if *share >= 100.0 - NEGLIGIBLE {
+                format!(
+                    r#"<circle cx="{}" cy="{}" r="{mid_radius}" fill="none" stroke="{color}" stroke-width="{}" />"#,
+                    CENTER.0,
+                    CENTER.1,
+                    OUTER_RADIUS - INNER_RADIUS,
+                )
+            } else {
+                format!(
+                    r#"<path d="{}" fill="{color}" />"#,
+                    donut_slice(CENTER, OUTER_RADIUS, INNER_RADIUS, start, end)
+                )
+            };
+            chart.push_str(&format!(
+                r#"<g class="slice" style="animation-delay: {}ms"><title>{} {percent}</title>{slice}</g>
+"#,
+                100 * idx,
+                escape_html(language),
+            ));
+
+            // small slices go without a label rather than with one spilling over its neighbours
+            let label = format!("{share:.0}%");
+            let arc_length = std::f64::consts::TAU * mid_radius * share / 100.0;
+            if Font::HelveticaBold.text_width(&label, 10.0) + 6.0 <= arc_length {
+                let (x, y) = point_on_circle(CENTER, mid_radius, (start + end) / 2.0);
+                chart.push_str(&format!(
+                    r#"<text class="slice-label" x="{x:.2}" y="{y:.2}" dy="0.35em" text-anchor="middle" fill="{}" style="animation-delay: {}ms">{label}</text>
+"#,
+                    label_color(color),
+                    100 * idx,
+                ));
+            }
+            start = end;
+
+            let x = LEGEND_LEFT + (idx / LEGEND_ROWS) as f64 * (COLUMN_WIDTH + COLUMN_GAP);
+            let top = LEGEND_TOP + (idx % LEGEND_ROWS) as f64 * ROW_PITCH;
+            legend.push_str(&language_item(
+                idx,
+                (x, top),
+                language,
+                color,
+                &percent,
+                COLUMN_WIDTH,
+            ));
+        }
+
+        let width = LEGEND_LEFT + columns as f64 * (COLUMN_WIDTH + COLUMN_GAP) + 13.0;
+        tags_map.insert("width".to_string(), width.to_string());
+        tags_map.insert("background_width".to_string(), (width - 10.0).to_string());
+        tags_map.insert("slices".to_string(), chart);
+        tags_map.insert("legend".to_string(), legend);
+
+        Self::replace_tags(svg_content, &tags_map)
+    }
+
+    /// The templates switch to dark colors when linked with `#gh-dark-mode-only`,
+    /// a card served without a fragment gets them applied unconditionally instead
+    pub fn apply_theme(svg: String, theme: Theme) -> String {
+        match theme {
+            Theme::Light => svg,
+            Theme::Dark => svg.replace("#gh-dark-mode-only:target", "#gh-dark-mode-only"),
+        }
+    }
+
+    /// Name of the dark variant of a card, e.g. `overview_dark.svg`
+    pub fn dark_variant(card: &str) -> String {
+        match card.strip_suffix(".svg") {
+            Some(stem) => format!("{stem}_dark.svg"),
+            None => format!("{card}_dark"),
+        }
+    }
+
+    /// Writes a rendered card along with its dark variant
+    fn write_themed(&self, name: &str, svg: String) -> Result<(), StatsError> {
+        self.write(
+            &Self::dark_variant(name),
+            Self::apply_theme(svg.clone(), Theme::Dark),
+        )?;
+        self.write(name, svg)
+    }
+
+    /// Writes a rendered card into the output folder
+    fn write(&self, name: &str, content: String) -> Result<(), StatsError> {
+        fs::create_dir_all(&self.output_folder)?;
+        fs::write(format!("{}/{}", self.output_folder, name), content)?;
+        Ok(())
+    }
+
+    /// Reads a template from the template folder
+    fn read_template(&self, name: &str) -> Result<String, StatsError> {
+        Self::read_file(format!("{}/{}", self.template_folder, name))
+    }
+
+    /// Reads a template, telling a missing one apart from other IO errors
+    fn read_file(path: String) -> Result<String, StatsError> {
+        fs::read_to_string(&path).map_err(|source| match source.kind() {
+            io::ErrorKind::NotFound => StatsError::TemplateMissing { path, source },
+            _ => StatsError::Io(source),
+        })
+    }
+
     fn replace_tags(
         content: String,
         replacements: &HashMap<String, String>,
-    ) -> Result<String, anyhow::Error> {
+    ) -> Result<String, StatsError> {
         let mut modified_content = content;
         for (tag, value) in replacements {
             let pattern = format!(r"{{{{ {tag} }}}}");
@@ -168,3 +822,307 @@ impl ImageGen {
         Ok(modified_content)
     }
 }
+
+fn or_unavailable(value: Option<impl std::fmt::Display>) -> String {
+    value
+        .map(|value| value.to_string())
+        .unwrap_or_else(|| UNAVAILABLE.to_string())
+}
+
+/// A table cell sortable by its value, unavailable values sort first
+fn number_cell(value: Option<i64>) -> String {
+    format!(
+        r#"<td class="number" data-value="{}">{}</td>"#,
+        value.unwrap_or(-1),
+        or_unavailable(value)
+    )
+}
+
+/// A language's dot, name and share, the name shortened to fit into `width`
+fn language_item(
+    idx: usize,
+    (x, top): (f64, f64),
+    language: &str,
+    color: &str,
+    percent: &str,
+    width: f64,
+) -> String {
+    let name = Font::HelveticaBold.truncate(
+        language,
+        12.0,
+        width - 25.0 - Font::Helvetica.text_width(percent, 12.0),
+    );
+    format!(
+        r#"<g class="lang-item" style="animation-delay: {}ms">
+<circle cx="{}" cy="{}" r="4" fill="{}" />
+<text x="{}" y="{}"><tspan class="lang">{}</tspan><tspan class="percent" dx="4">{}</tspan></text>
+</g>
+"#,
+        150 * idx,
+        x + 8.0,
+        top + 8.0,
+        color,
+        x + 21.0,
+        top + 12.0,
+        escape_html(&name),
+        percent
+    )
+}
+
+/// The point `turn` of the way around a circle, clockwise from its top
+fn point_on_circle(center: (f64, f64), radius: f64, turn: f64) -> (f64, f64) {
+    let angle = turn * std::f64::consts::TAU - std::f64::consts::FRAC_PI_2;
+    (
+        center.0 + radius * angle.cos(),
+        center.1 + radius * angle.sin(),
+    )
+}
+
+/// Path of the ring segment between the turns `start` and `end`, outer arc clockwise
+/// and inner arc back
+fn donut_slice(center: (f64, f64), outer: f64, inner: f64, start: f64, end: f64) -> String {
+    let large_arc = u8::from(end - start > 0.5);
+    let (x1, y1) = point_on_circle(center, outer, start);
+    let (x2, y2) = point_on_circle(center, outer, end);
+    let (x3, y3) = point_on_circle(center, inner, end);
+    let (x4, y4) = point_on_circle(center, inner, start);
+    format!(
+        "M {x1:.2} {y1:.2} A {outer} {outer} 0 {large_arc} 1 {x2:.2} {y2:.2} \
+         L {x3:.2} {y3:.2} A {inner} {inner} 0 {large_arc} 0 {x4:.2} {y4:.2} Z"
+    )
+}
+
+/// Dark text on light slices, white on the others
+fn label_color(background: &str) -> &'static str {
+    let channel = |range| {
+        background
+            .strip_prefix('#')
+            .and_then(|hex| hex.get(range))
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+    };
+    match (channel(0..2), channel(2..4), channel(4..6)) {
+        (Some(r), Some(g), Some(b))
+            if 0.299 * r as f64 + 0.587 * g as f64 + 0.114 * b as f64 > 150.0 =>
+        {
+            "#24292e"
+        }
+        _ => "#ffffff",
+    }
+}
+
+/// Embeds an SVG as a `data:` URI, so the page doesn't load anything else
+fn svg_data_uri(svg: &str) -> String {
+    format!("data:image/svg+xml;base64,{}", STANDARD.encode(svg))
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use serde_json::json;
+
+    use super::*;
+    use crate::{
+        domain::{ContributionDay, ContributionWeek, Language, Metric},
+        service::test_support::{language, stats_builder},
+    };
+
+    fn image_gen(output_folder: &str) -> ImageGen {
+        ImageGen::new("resources/templates".to_string(), output_folder.to_string())
+    }
+
+    fn stats(name: &str, contributions: i64, calendar: Vec<ContributionWeek>) -> Stats {
+        stats_builder()
+            .name(name.to_string())
+            .total_contributions(Metric::Available(contributions))
+            .contribution_calendar(Metric::Available(calendar))
+            .build()
+            .unwrap()
+    }
+
+    fn donut(shares: &[(&str, f64)]) -> String {
+        let languages = shares
+            .iter()
+            .map(|(name, share)| {
+                let mut language = Language::share(name.to_string(), *share, "#dea584".to_string());
+                // shares are in basis points of the whole
+                language.set_proportion(10_000);
+                (name.to_string(), language)
+            })
+            .collect();
+        let stats = stats_builder().languages(languages).build().unwrap();
+        image_gen("generated")
+            .with_languages_chart(LanguagesChart::Donut)
+            .render_languages(&stats, None)
+            .unwrap()
+    }
+
+    fn assert_point(actual: (f64, f64), expected: (f64, f64)) {
+        assert!(
+            (actual.0 - expected.0).abs() < 1e-9 && (actual.1 - expected.1).abs() < 1e-9,
+            "{actual:?} != {expected:?}"
+        );
+    }
+
+    #[test]
+    fn points_on_circle_go_clockwise_from_the_top() {
+        assert_point(point_on_circle((10.0, 10.0), 5.0, 0.0), (10.0, 5.0));
+        assert_point(point_on_circle((10.0, 10.0), 5.0, 0.25), (15.0, 10.0));
+        assert_point(point_on_circle((10.0, 10.0), 5.0, 0.5), (10.0, 15.0));
+        assert_point(point_on_circle((10.0, 10.0), 5.0, 0.75), (5.0, 10.0));
+        assert_point(point_on_circle((10.0, 10.0), 5.0, 1.0), (10.0, 5.0));
+    }
+
+    #[test]
+    fn donut_slices_run_along_both_rings() {
+        assert_eq!(
+            donut_slice((0.0, 0.0), 10.0, 5.0, 0.0, 0.25),
+            "M 0.00 -10.00 A 10 10 0 0 1 10.00 0.00 L 5.00 0.00 A 5 5 0 0 0 0.00 -5.00 Z"
+        );
+        // past half the ring, both arcs take the long way round
+        assert_eq!(
+            donut_slice((0.0, 0.0), 10.0, 5.0, 0.0, 0.75),
+            "M 0.00 -10.00 A 10 10 0 1 1 -10.00 0.00 L -5.00 0.00 A 5 5 0 1 0 0.00 -5.00 Z"
+        );
+    }
+
+    #[test]
+    fn languages_past_the_legend_are_grouped_as_other() {
+        let svg = donut(&[
+            ("Rust", 30.0),
+            ("Go", 20.0),
+            ("C", 10.0),
+            ("Java", 10.0),
+            ("Lua", 10.0),
+            ("Nix", 5.0),
+            ("Perl", 5.0),
+            ("Ruby", 5.0),
+            ("Shell", 5.0),
+        ]);
+
+        assert!(svg.contains(
+            r#"<tspan class="lang">Nix</tspan><tspan class="percent" dx="4">5.00%</tspan>"#
+        ));
+        assert!(svg.contains(
+            r#"<tspan class="lang">Other</tspan><tspan class="percent" dx="4">15.00%</tspan>"#
+        ));
+        assert!(!svg.contains("Perl"));
+        assert_eq!(svg.matches("<path").count(), 7);
+    }
+
+    #[test]
+    fn a_full_legend_needs_no_other() {
+        let svg = donut(&[
+            ("Rust", 40.0),
+            ("Go", 20.0),
+            ("C", 10.0),
+            ("Java", 10.0),
+            ("Lua", 10.0),
+            ("Nix", 5.0),
+            ("Perl", 5.0),
+        ]);
+
+        assert!(svg.contains(r#"<tspan class="lang">Perl</tspan>"#));
+        assert!(!svg.contains("Other"));
+    }
+
+    #[test]
+    fn a_single_language_is_a_full_ring() {
+        let svg = donut(&[("Rust", 100.0)]);
+
+        assert!(!svg.contains("<path"));
+        assert!(svg.contains(
+            r##"<circle cx="87" cy="123" r="53" fill="none" stroke="#dea584" stroke-width="26" />"##
+        ));
+        assert!(svg.contains(">100%</text>"));
+        assert!(!svg.contains("Other"));
+    }
+
+    #[test]
+    fn custom_cards_are_plain_svg_sized_by_their_rows() {
+        let card: CustomCard = serde_json::from_value(json!({
+            "template": "resources/templates/custom_card.svg",
+            "output": "card.svg",
+            "metrics": ["stars", "contributions"],
+        }))
+        .unwrap();
+
+        let svg = image_gen("generated")
+            .render_custom_card(&stats("jane", 12, vec![]), &card)
+            .unwrap();
+
+        assert!(!svg.contains("foreignObject"));
+        assert!(!svg.contains("{{"));
+        // the rows line up with the overview card's, one row height apart
+        assert!(svg.contains(r#"<text class="label" x="26" y="68">Stars</text>"#));
+        assert!(svg.contains(r#"<text class="label" x="26" y="92">Contributions</text>"#));
+        assert!(svg.contains(r#"width="360" height="114""#));
+        assert!(svg.contains(r#"height="104" rx="6""#));
+    }
+
+    #[test]
+    fn contribution_cells_sit_in_their_weekday_row() {
+        // 2024-01-03 is a Wednesday, the first week starts mid-week
+        let day = |day| ContributionDay::new(NaiveDate::from_ymd_opt(2024, 1, day).unwrap(), 1, 1);
+        let calendar = vec![
+            ContributionWeek::new(vec![day(3), day(4)]),
+            ContributionWeek::new(vec![day(7)]),
+        ];
+
+        let svg = image_gen("generated")
+            .render_contributions_grid(&stats("jane", 3, calendar))
+            .unwrap();
+
+        assert!(!svg.contains("foreignObject"));
+        assert!(svg.contains(r#"<rect x="40" y="86" width="10" height="10""#));
+        assert!(svg.contains(r#"<rect x="40" y="98" width="10" height="10""#));
+        assert!(svg.contains(r#"<rect x="52" y="50" width="10" height="10""#));
+    }
+
+    #[test]
+    fn leaderboards_rank_members_in_plain_svg() {
+        let output_folder =
+            std::env::temp_dir().join(format!("github-stats-leaderboard-{}", std::process::id()));
+        fs::create_dir_all(&output_folder).unwrap();
+        let team = vec![
+            ("jane".to_string(), stats("Jane <Doe>", 3, vec![])),
+            ("john".to_string(), stats("", 7, vec![])),
+        ];
+
+        image_gen(output_folder.to_str().unwrap())
+            .generate_leaderboard(&team)
+            .unwrap();
+
+        let svg = fs::read_to_string(output_folder.join("leaderboard.svg")).unwrap();
+        fs::remove_dir_all(&output_folder).unwrap();
+        assert!(!svg.contains("foreignObject"));
+        assert!(svg.contains(r#"<text x="50" y="68">john</text>"#));
+        assert!(svg.contains(r#"<text x="50" y="92">Jane &lt;Doe&gt;</text>"#));
+        assert!(svg.contains(r#"<text x="50" y="116">Team</text>"#));
+        assert!(svg.contains(r#"<text class="number" x="274" y="116">10</text>"#));
+        // the heading, two members and the total
+        assert!(svg.contains(r#"width="480" height="138""#));
+    }
+
+    #[test]
+    fn dashboard_leaves_the_bytes_of_shares_unavailable() {
+        let mut share = Language::share("Go".to_string(), 25.0, "#00add8".to_string());
+        share.set_proportion(10_000);
+        let stats = stats_builder()
+            .languages(vec![language("Rust", 300), ("Go".to_string(), share)])
+            .build()
+            .unwrap();
+
+        let html = image_gen("generated").render_dashboard(&stats).unwrap();
+
+        assert!(html.contains(r#"Rust</td><td class="number" data-value="300">300</td>"#));
+        assert!(html.contains(r#"Go</td><td class="number" data-value="-1">n/a</td>"#));
+        assert!(!html.contains(">2500<"));
+    }
+}
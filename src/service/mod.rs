@@ -1,7 +1,33 @@
+mod auth;
+mod cache;
 mod configuration;
+mod csv_export;
+mod git_history;
+mod gitea;
 mod github;
+mod gitlab;
 mod image_gen;
+mod openmetrics;
+mod readme;
+mod response;
+mod server;
+#[cfg(test)]
+pub mod test_support;
+mod text_width;
+mod webhook;
 
+pub use auth::*;
+pub use cache::*;
 pub use configuration::*;
+pub use csv_export::*;
+pub use git_history::*;
+pub use gitea::*;
 pub use github::*;
+pub use gitlab::*;
 pub use image_gen::*;
+pub use openmetrics::*;
+pub use readme::*;
+pub use response::*;
+pub use server::*;
+pub use text_width::*;
+pub use webhook::*;
@@ -0,0 +1,170 @@
+use std::{fs, path::Path};
+
+use anyhow::{Context, Result};
+
+use crate::domain::Stats;
+
+/// Content type of the OpenMetrics text exposition format
+pub const OPENMETRICS_CONTENT_TYPE: &str =
+    "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+/// One metric family: its name, help text and a sample per labelled value
+struct Family {
+    name: &'static str,
+    help: &'static str,
+    samples: Vec<(Vec<(&'static str, String)>, f64)>,
+}
+
+impl Family {
+    fn new(name: &'static str, help: &'static str) -> Self {
+        Self {
+            name,
+            help,
+            samples: vec![],
+        }
+    }
+
+    fn add(&mut self, labels: Vec<(&'static str, String)>, value: impl Into<Option<f64>>) {
+        if let Some(value) = value.into() {
+            self.samples.push((labels, value));
+        }
+    }
+}
+
+/// Renders the statistics of each `(login, stats)` pair as gauges in the OpenMetrics
+/// text format, every sample labelled with its `account`.
+///
+/// Unavailable metrics have no sample, `github_stats_metric_available` tells which.
+pub fn render_openmetrics(accounts: &[(&str, &Stats)]) -> String {
+    let mut stargazers = Family::new("github_stats_stargazers", "Stars across the repositories");
+    let mut forks = Family::new("github_stats_forks", "Forks across the repositories");
+    let mut repositories = Family::new("github_stats_repositories", "Repositories counted");
+    let mut contributions = Family::new("github_stats_contributions", "Contributions, all time");
+    let mut lines_added = Family::new("github_stats_lines_added", "Lines added by the account");
+    let mut lines_deleted =
+        Family::new("github_stats_lines_deleted", "Lines deleted by the account");
+    let mut views = Family::new("github_stats_views", "Repository views, last 14 days");
+    let mut reviews = Family::new("github_stats_reviews", "Pull request reviews, past year");
+    let mut language_bytes = Family::new(
+        "github_stats_language_bytes",
+        "Bytes of code in the language",
+    );
+    let mut language_proportion = Family::new(
+        "github_stats_language_proportion",
+        "Share of the code in the language, from 0 to 1",
+    );
+    let mut repository_stargazers = Family::new(
+        "github_stats_repository_stargazers",
+        "Stars of a single repository",
+    );
+    let mut available = Family::new(
+        "github_stats_metric_available",
+        "Whether the metric could be collected",
+    );
+
+    for (login, stats) in accounts {
+        let account = || vec![("account", login.to_string())];
+        let lines_changed = stats.lines_changed();
+
+        stargazers.add(account(), stats.stargazers() as f64);
+        forks.add(account(), stats.forks() as f64);
+        repositories.add(account(), stats.repos().len() as f64);
+        contributions.add(account(), stats.total_contributions().map(|c| c as f64));
+        lines_added.add(account(), lines_changed.map(|(added, _)| added as f64));
+        lines_deleted.add(account(), lines_changed.map(|(_, deleted)| deleted as f64));
+        views.add(account(), stats.views().map(|v| v as f64));
+        reviews.add(account(), stats.reviews().map(|r| r as f64));
+
+        for (name, language) in stats.all_languages() {
+            let labels = || vec![("account", login.to_string()), ("language", name.clone())];
+            // no sample for forges that only report shares
+            language_bytes.add(labels(), language.bytes().map(|bytes| bytes as f64));
+            language_proportion.add(labels(), language.proportion() / 100.0);
+        }
+        for repo in stats.repos() {
+            repository_stargazers.add(
+                vec![
+                    ("account", login.to_string()),
+                    ("repository", repo.name_with_owner().to_string()),
+                ],
+                repo.stargazers() as f64,
+            );
+        }
+
+        for (metric, is_available) in stats.availability() {
+            available.add(
+                vec![
+                    ("account", login.to_string()),
+                    ("metric", metric.to_string()),
+                ],
+                if is_available { 1.0 } else { 0.0 },
+            );
+        }
+    }
+
+    let mut output = String::new();
+    for family in [
+        stargazers,
+        forks,
+        repositories,
+        contributions,
+        lines_added,
+        lines_deleted,
+        views,
+        reviews,
+        language_bytes,
+        language_proportion,
+        repository_stargazers,
+        available,
+    ] {
+        output.push_str(&format!("# HELP {} {}\n", family.name, family.help));
+        output.push_str(&format!("# TYPE {} gauge\n", family.name));
+        for (labels, value) in family.samples {
+            let labels = labels
+                .iter()
+                .map(|(name, value)| format!("{name}=\"{}\"", escape_label(value)))
+                .collect::<Vec<_>>()
+                .join(",");
+            output.push_str(&format!("{}{{{}}} {}\n", family.name, labels, value));
+        }
+    }
+    output.push_str("# EOF\n");
+
+    output
+}
+
+/// Writes the metrics for the node_exporter textfile collector. The file is
+/// replaced atomically so the collector never reads it half written.
+pub fn write_metrics_textfile(path: &str, accounts: &[(&str, &Stats)]) -> Result<()> {
+    let temporary = Path::new(path).with_extension("prom.tmp");
+    fs::write(&temporary, render_openmetrics(accounts))
+        .with_context(|| format!("Failed to write {}", temporary.display()))?;
+    fs::rename(&temporary, path).with_context(|| format!("Failed to replace {path}"))?;
+    Ok(())
+}
+
+/// Escapes a label value as required by the text format
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', r"\\")
+        .replace('"', r#"\""#)
+        .replace('\n', r"\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service::test_support::stats;
+
+    #[test]
+    fn every_language_is_exported_not_only_the_cards_top() {
+        let stats = stats();
+
+        let rendered = render_openmetrics(&[("jane", &stats)]);
+
+        assert!(rendered
+            .contains(r#"github_stats_language_bytes{account="jane",language="Shell"} 100"#));
+        assert!(rendered
+            .contains(r#"github_stats_language_proportion{account="jane",language="Shell"} 0.25"#));
+    }
+}
@@ -0,0 +1,195 @@
+use std::fs;
+
+use anyhow::{Context, Result};
+
+use crate::domain::Stats;
+
+use super::ImageGen;
+
+const START_MARKER: &str = "<!-- github-stats:start -->";
+const END_MARKER: &str = "<!-- github-stats:end -->";
+
+/// The cards embedded in the README, with their alt text
+const CARDS: [(&str, &str); 3] = [
+    ("overview.svg", "GitHub statistics overview"),
+    ("languages.svg", "Most used languages"),
+    ("contribution_grid.svg", "Contribution calendar"),
+];
+
+/// Keeps the section of a markdown file between the `github-stats` marker
+/// comments in sync with the generated cards. Everything outside the markers
+/// is left untouched.
+#[derive(Debug, Clone)]
+pub struct ReadmeUpdater {
+    path: String,
+    image_base_url: String,
+    text_metrics: bool,
+}
+
+impl ReadmeUpdater {
+    pub fn new(path: String, image_base_url: String, text_metrics: bool) -> Self {
+        Self {
+            path,
+            image_base_url,
+            text_metrics,
+        }
+    }
+
+    /// Rewrites the section between the markers, returns whether the file changed.
+    /// The file isn't written at all when the section is already up to date.
+    #[tracing::instrument(skip(stats))]
+    pub fn update(&self, stats: &Stats) -> Result<bool> {
+        let content = fs::read_to_string(&self.path)
+            .with_context(|| format!("Failed to read {}", self.path))?;
+        let updated = replace_section(&content, &self.render(stats))
+            .with_context(|| format!("Failed to update {}", self.path))?;
+
+        if updated == content {
+            return Ok(false);
+        }
+        fs::write(&self.path, updated).with_context(|| format!("Failed to write {}", self.path))?;
+        Ok(true)
+    }
+
+    /// The markdown placed between the markers, without the markers themselves
+    pub fn render(&self, stats: &Stats) -> String {
+        let base = self.image_base_url.trim_end_matches('/');
+        let mut section = CARDS
+            .iter()
+            .map(|(card, alt)| {
+                let dark = ImageGen::dark_variant(card);
+                format!(
+                    r#"<picture>
+  <source media="(prefers-color-scheme: dark)" srcset="{base}/{dark}">
+  <img alt="{alt}" src="{base}/{card}">
+</picture>"#
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        if self.text_metrics {
+            section.push_str("\n\n");
+            section.push_str(&text_metrics(stats));
+        }
+
+        section
+    }
+}
+
+/// A one-line summary of the statistics, for readers that don't load images
+fn text_metrics(stats: &Stats) -> String {
+    let or_unavailable =
+        |value: Option<i64>| value.map_or_else(|| "n/a".to_string(), |value| value.to_string());
+    let lines_changed = stats
+        .lines_changed()
+        .map(|(added, deleted)| added + deleted);
+
+    [
+        format!("**{}** stars", stats.stargazers()),
+        format!("**{}** forks", stats.forks()),
+        format!(
+            "**{}** contributions",
+            or_unavailable(stats.total_contributions())
+        ),
+        format!("**{}** lines changed", or_unavailable(lines_changed)),
+        format!("**{}** repositories", stats.repos().len()),
+    ]
+    .join(" · ")
+}
+
+/// Replaces whatever is between the first start marker and the end marker after it
+fn replace_section(content: &str, section: &str) -> Result<String> {
+    let start = content
+        .find(START_MARKER)
+        .with_context(|| format!("{START_MARKER} not found"))?;
+    let inner_start = start + START_MARKER.len();
+    let end = content[inner_start..]
+        .find(END_MARKER)
+        .map(|end| inner_start + end)
+        .with_context(|| format!("{END_MARKER} not found after {START_MARKER}"))?;
+
+    Ok(format!(
+        "{}\n{}\n{}",
+        &content[..inner_start],
+        section,
+        &content[end..]
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::{Metric, StatsBuilder};
+
+    use super::*;
+
+    fn stats() -> Stats {
+        StatsBuilder::default()
+            .name("jane".to_string())
+            .stargazers(3)
+            .forks(1)
+            .languages(vec![])
+            .repos(vec![])
+            .total_contributions(Metric::Available(12))
+            .lines_changed_by_repo(Metric::Unavailable("no clones".to_string()))
+            .views(Metric::Available(vec![]))
+            .contribution_calendar(Metric::Available(vec![]))
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn section_is_replaced_between_the_markers_only() {
+        let content = format!("# Jane\n{START_MARKER}\nold cards\n{END_MARKER}\nfooter\n");
+
+        let updated = replace_section(&content, "new cards").unwrap();
+
+        assert_eq!(
+            updated,
+            format!("# Jane\n{START_MARKER}\nnew cards\n{END_MARKER}\nfooter\n")
+        );
+    }
+
+    #[test]
+    fn replacing_the_same_section_again_changes_nothing() {
+        let content = format!("{START_MARKER}{END_MARKER}");
+
+        let once = replace_section(&content, "cards").unwrap();
+        let twice = replace_section(&once, "cards").unwrap();
+
+        assert_eq!(once, twice);
+    }
+
+    #[test]
+    fn missing_or_unbalanced_markers_are_an_error() {
+        assert!(replace_section("no markers", "cards").is_err());
+        assert!(replace_section(&format!("{START_MARKER}\nopen"), "cards").is_err());
+        assert!(replace_section(&format!("closed\n{END_MARKER}"), "cards").is_err());
+        assert!(replace_section(&format!("{END_MARKER}\n{START_MARKER}"), "cards").is_err());
+    }
+
+    #[test]
+    fn cards_link_a_dark_variant_without_fragments() {
+        let updater = ReadmeUpdater::new(
+            "README.md".to_string(),
+            "https://example.com/cards/".to_string(),
+            false,
+        );
+
+        let section = updater.render(&stats());
+
+        assert!(!section.contains('#'));
+        assert!(section.contains(r#"srcset="https://example.com/cards/overview_dark.svg""#));
+        assert!(section.contains(r#"src="https://example.com/cards/overview.svg""#));
+    }
+
+    #[test]
+    fn text_metrics_show_unavailable_metrics_as_na() {
+        let updater = ReadmeUpdater::new("README.md".to_string(), String::new(), true);
+
+        let section = updater.render(&stats());
+
+        assert!(section.contains("**12** contributions"));
+        assert!(section.contains("**n/a** lines changed"));
+    }
+}
@@ -0,0 +1,131 @@
+use chrono::{DateTime, Utc};
+use reqwest::{header::HeaderMap, Response, StatusCode};
+
+use crate::domain::StatsError;
+
+/// Turns the status codes forges use for rejected tokens, rate limiting and
+/// missing resources into their `StatsError`
+pub async fn check_status(response: Response, what: &str) -> Result<Response, StatsError> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+
+    // GitHub sends `x-ratelimit-*`, GitLab the unprefixed `ratelimit-*` headers
+    let rate_limited = ["x-ratelimit-remaining", "ratelimit-remaining"]
+        .iter()
+        .any(|name| {
+            response
+                .headers()
+                .get(*name)
+                .is_some_and(|remaining| remaining == "0")
+        })
+        || response.headers().contains_key("retry-after");
+
+    match status {
+        StatusCode::FORBIDDEN if rate_limited => Err(StatsError::RateLimited {
+            reset_at: rate_limit_reset(response.headers()),
+        }),
+        StatusCode::TOO_MANY_REQUESTS => Err(StatsError::RateLimited {
+            reset_at: rate_limit_reset(response.headers()),
+        }),
+        StatusCode::UNAUTHORIZED => Err(StatsError::auth(format!("{what} rejected the token"))),
+        StatusCode::FORBIDDEN => {
+            let message = response.text().await.unwrap_or_default();
+            Err(StatsError::auth(format!("{what} is forbidden: {message}")))
+        }
+        StatusCode::NOT_FOUND => Err(StatsError::NotFound(what.to_string())),
+        _ => Err(response.error_for_status().unwrap_err().into()),
+    }
+}
+
+/// When the rate limit resets, from the `x-ratelimit-reset` or `ratelimit-reset` epoch seconds
+pub fn rate_limit_reset(headers: &HeaderMap) -> Option<DateTime<Utc>> {
+    headers
+        .get("x-ratelimit-reset")
+        .or_else(|| headers.get("ratelimit-reset"))
+        .and_then(|reset| reset.to_str().ok())
+        .and_then(|reset| reset.parse::<i64>().ok())
+        .and_then(|reset| DateTime::from_timestamp(reset, 0))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{http::StatusCode as Status, routing::get, Router};
+
+    use super::*;
+    use crate::service::test_support::stand_in;
+
+    /// A stand-in answering `/{status}` with that status and the given headers
+    async fn status_server(headers: &'static [(&'static str, &'static str)]) -> String {
+        let app = Router::new().route(
+            "/{status}",
+            get(
+                move |axum::extract::Path(status): axum::extract::Path<u16>| async move {
+                    let mut response = axum::response::Response::new(axum::body::Body::empty());
+                    *response.status_mut() = Status::from_u16(status).unwrap();
+                    for (name, value) in headers {
+                        response.headers_mut().insert(*name, value.parse().unwrap());
+                    }
+                    response
+                },
+            ),
+        );
+        let url = stand_in(app).await;
+
+        url
+    }
+
+    async fn check(url: &str, status: u16) -> Result<Response, StatsError> {
+        let response = reqwest::get(format!("{url}/{status}")).await.unwrap();
+        check_status(response, "test").await
+    }
+
+    #[tokio::test]
+    async fn maps_rejected_tokens_and_missing_resources() {
+        let url = status_server(&[]).await;
+
+        assert!(check(&url, 200).await.is_ok());
+        assert!(matches!(
+            check(&url, 401).await,
+            Err(StatsError::Auth { .. })
+        ));
+        assert!(matches!(
+            check(&url, 403).await,
+            Err(StatsError::Auth { .. })
+        ));
+        assert!(matches!(
+            check(&url, 404).await,
+            Err(StatsError::NotFound(_))
+        ));
+        assert!(matches!(check(&url, 500).await, Err(StatsError::Http(_))));
+    }
+
+    #[tokio::test]
+    async fn reads_github_rate_limit_headers() {
+        let url = status_server(&[
+            ("x-ratelimit-remaining", "0"),
+            ("x-ratelimit-reset", "1792400000"),
+        ])
+        .await;
+
+        let Err(StatsError::RateLimited { reset_at }) = check(&url, 403).await else {
+            panic!("expected a rate limit error");
+        };
+        assert_eq!(reset_at.unwrap().timestamp(), 1_792_400_000);
+    }
+
+    #[tokio::test]
+    async fn reads_gitlab_rate_limit_headers() {
+        let url = status_server(&[
+            ("ratelimit-remaining", "0"),
+            ("ratelimit-reset", "1792400000"),
+        ])
+        .await;
+
+        let Err(StatsError::RateLimited { reset_at }) = check(&url, 429).await else {
+            panic!("expected a rate limit error");
+        };
+        assert_eq!(reset_at.unwrap().timestamp(), 1_792_400_000);
+    }
+}
@@ -0,0 +1,696 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    future::Future,
+    hash::{DefaultHasher, Hash, Hasher},
+    pin::Pin,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+use axum::{
+    body::Bytes,
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Router,
+};
+use serde::Deserialize;
+use tokio::sync::{mpsc, Mutex};
+
+use crate::{
+    algebra::ImageGenExt,
+    domain::{Stats, StatsChange, StatsError, StatsMetric, StatsSummary},
+};
+
+use super::{
+    render_openmetrics, Configuration, ImageGen, Theme, Webhook, OPENMETRICS_CONTENT_TYPE,
+};
+
+/// Collects the statistics of the account a configuration points to
+pub type StatsLoader =
+    Arc<dyn Fn(Configuration) -> Pin<Box<dyn Future<Output = Result<Stats>> + Send>> + Send + Sync>;
+
+/// Collects the parts of previously collected statistics made stale by webhook events
+pub type StatsRefresher = Arc<
+    dyn Fn(
+            Configuration,
+            Arc<Stats>,
+            Vec<StatsChange>,
+        ) -> Pin<Box<dyn Future<Output = Result<Stats>> + Send>>
+        + Send
+        + Sync,
+>;
+
+/// Serves the cards and the statistics over HTTP, collecting the statistics
+/// again once they're older than the cache TTL.
+///
+/// The configured account is served at the root, team members under `/<login>/`.
+/// When a webhook secret is configured, events posted to `/webhook` refresh the
+/// affected parts of the cached statistics once the burst they came in settles.
+#[derive(Clone)]
+pub struct StatsServer {
+    configuration: Configuration,
+    image_gen: Arc<ImageGen>,
+    loader: StatsLoader,
+    refresher: StatsRefresher,
+    webhook: Option<Webhook>,
+    /// Queue of the changes reported by webhook events, only set while serving
+    events: Option<mpsc::UnboundedSender<Vec<StatsChange>>>,
+    /// One slot per account, locked while the account's statistics are collected
+    /// so concurrent requests wait for the same collection
+    entries: Arc<std::sync::Mutex<HashMap<String, CacheSlot>>>,
+}
+
+type CacheSlot = Arc<Mutex<Option<CachedStats>>>;
+
+struct CachedStats {
+    stats: Arc<Stats>,
+    collected_at: Instant,
+}
+
+#[derive(Debug, Deserialize)]
+struct CardQuery {
+    #[serde(default)]
+    theme: Theme,
+    limit: Option<usize>,
+}
+
+impl StatsServer {
+    pub fn new(
+        configuration: Configuration,
+        image_gen: ImageGen,
+        loader: StatsLoader,
+        refresher: StatsRefresher,
+    ) -> Self {
+        let webhook = configuration.webhook_secret().cloned().map(Webhook::new);
+        Self {
+            configuration,
+            image_gen: Arc::new(image_gen),
+            loader,
+            refresher,
+            webhook,
+            events: None,
+            entries: Arc::default(),
+        }
+    }
+
+    pub fn router(self) -> Router {
+        Router::new()
+            .route("/webhook", post(webhook))
+            .route("/{file}", get(root_file))
+            .route("/{login}/{file}", get(member_file))
+            .with_state(self)
+    }
+
+    /// Serves until `shutdown` completes, letting in-flight requests finish
+    pub async fn serve(self, shutdown: impl Future<Output = ()> + Send + 'static) -> Result<()> {
+        let listener = tokio::net::TcpListener::bind(self.configuration.serve_address()).await?;
+        tracing::info!("Serving statistics on {}", listener.local_addr()?);
+
+        axum::serve(listener, self.receiving_events().router())
+            .with_graceful_shutdown(shutdown)
+            .await?;
+        Ok(())
+    }
+
+    /// Queues webhook events, applied in the background when a secret is configured
+    fn receiving_events(self) -> Self {
+        let (events, received) = mpsc::unbounded_channel();
+        let server = Self {
+            events: Some(events),
+            ..self
+        };
+        if server.webhook.is_some() {
+            tokio::spawn(server.clone().apply_changes(received));
+        }
+        server
+    }
+
+    /// The cached statistics of `login`, or of the configured account when `None`,
+    /// with how long they stay fresh
+    async fn stats(&self, login: Option<&str>) -> Result<(Arc<Stats>, Duration), StatsError> {
+        let key = login.unwrap_or_default().to_lowercase();
+        let entry = self
+            .entries
+            .lock()
+            .expect("cache lock poisoned")
+            .entry(key)
+            .or_default()
+            .clone();
+
+        let mut entry = entry.lock().await;
+        let ttl = self.configuration.cache_ttl();
+        if let Some(cached) = entry
+            .as_ref()
+            .filter(|cached| cached.collected_at.elapsed() < ttl)
+        {
+            return Ok((cached.stats.clone(), ttl - cached.collected_at.elapsed()));
+        }
+
+        let stats = Arc::new((self.loader)(self.configuration_for(login)).await?);
+        *entry = Some(CachedStats {
+            stats: stats.clone(),
+            collected_at: Instant::now(),
+        });
+
+        Ok((stats, ttl))
+    }
+
+    async fn respond(
+        &self,
+        login: Option<&str>,
+        file: &str,
+        query: &CardQuery,
+        headers: &HeaderMap,
+    ) -> Result<Response, StatsError> {
+        const FILES: [&str; 5] = [
+            "overview.svg",
+            "languages.svg",
+            "contribution_grid.svg",
+            "stats.json",
+            "metrics",
+        ];
+        // the dark variants written next to the cards are served under the same names
+        let dark_card = FILES[..3]
+            .iter()
+            .find(|card| ImageGen::dark_variant(card) == file);
+        let (file, theme) = match dark_card {
+            Some(card) => (*card, Theme::Dark),
+            None => (file, query.theme),
+        };
+        let badge = file
+            .strip_prefix("badge_")
+            .and_then(|name| name.strip_suffix(".svg"))
+            .and_then(|name| name.parse::<StatsMetric>().ok());
+        let card = self
+            .configuration
+            .cards()
+            .iter()
+            .find(|card| card.output() == file);
+        if !FILES.contains(&file) && badge.is_none() && card.is_none() {
+            return Ok(StatusCode::NOT_FOUND.into_response());
+        }
+
+        let (stats, fresh_for) = self.stats(login).await?;
+        let (content_type, body) = match (file, badge, card) {
+            (_, Some(metric), _) => (
+                "image/svg+xml",
+                self.image_gen.render_badge(&stats, metric)?,
+            ),
+            (_, _, Some(card)) => (
+                "image/svg+xml",
+                self.image_gen.render_custom_card(&stats, card)?,
+            ),
+            ("overview.svg", ..) => ("image/svg+xml", self.image_gen.render_overview(&stats)?),
+            ("languages.svg", ..) => (
+                "image/svg+xml",
+                self.image_gen.render_languages(&stats, query.limit)?,
+            ),
+            ("contribution_grid.svg", ..) => (
+                "image/svg+xml",
+                self.image_gen.render_contributions_grid(&stats)?,
+            ),
+            ("stats.json", ..) => (
+                "application/json",
+                serde_json::to_string(&StatsSummary::from(stats.as_ref()))
+                    .map_err(anyhow::Error::from)?,
+            ),
+            ("metrics", ..) => (
+                OPENMETRICS_CONTENT_TYPE,
+                render_openmetrics(&[(self.configuration_for(login).account(), &stats)]),
+            ),
+            _ => unreachable!("checked against FILES above"),
+        };
+        let body = match content_type {
+            "image/svg+xml" => ImageGen::apply_theme(body, theme),
+            _ => body,
+        };
+
+        let etag = etag(&body);
+        let cache_control = format!("public, max-age={}", fresh_for.as_secs());
+        let not_modified = headers
+            .get_all(header::IF_NONE_MATCH)
+            .iter()
+            .filter_map(|if_none_match| if_none_match.to_str().ok())
+            .any(|if_none_match| etag_matches(if_none_match, &etag));
+
+        let mut response = if not_modified {
+            StatusCode::NOT_MODIFIED.into_response()
+        } else {
+            ([(header::CONTENT_TYPE, content_type)], body).into_response()
+        };
+        let response_headers = response.headers_mut();
+        response_headers.insert(header::ETAG, header_value(&etag));
+        response_headers.insert(header::CACHE_CONTROL, header_value(&cache_control));
+
+        Ok(response)
+    }
+
+    /// Waits for each burst of webhook events to settle, then refreshes the
+    /// cached statistics once for all the changes of the burst
+    async fn apply_changes(self, mut received: mpsc::UnboundedReceiver<Vec<StatsChange>>) {
+        let debounce = self.configuration.webhook_debounce();
+        while let Some(changes) = received.recv().await {
+            let mut changes = changes.into_iter().collect::<BTreeSet<_>>();
+            while let Ok(Some(more)) = tokio::time::timeout(debounce, received.recv()).await {
+                changes.extend(more);
+            }
+            self.refresh(changes.into_iter().collect()).await;
+        }
+    }
+
+    /// Refreshes `changes` in every cached account. Requests keep getting the
+    /// previous statistics while the refresh runs.
+    async fn refresh(&self, changes: Vec<StatsChange>) {
+        let entries = self
+            .entries
+            .lock()
+            .expect("cache lock poisoned")
+            .iter()
+            .map(|(key, entry)| (key.clone(), entry.clone()))
+            .collect::<Vec<_>>();
+
+        for (key, entry) in entries {
+            let login = Some(key.as_str()).filter(|login| !login.is_empty());
+            if changes.contains(&StatsChange::Everything) {
+                tracing::info!(
+                    "Invalidating the statistics of {}",
+                    login.unwrap_or("the account")
+                );
+                *entry.lock().await = None;
+                continue;
+            }
+
+            let Some(previous) = entry
+                .lock()
+                .await
+                .as_ref()
+                .map(|cached| cached.stats.clone())
+            else {
+                continue;
+            };
+            tracing::info!(
+                "Refreshing {:?} for {}",
+                changes,
+                login.unwrap_or("the account")
+            );
+            let refreshed = (self.refresher)(
+                self.configuration_for(login),
+                previous.clone(),
+                changes.clone(),
+            )
+            .await;
+
+            let mut entry = entry.lock().await;
+            // statistics collected from scratch in the meantime are already up to date
+            let Some(cached) = entry
+                .as_mut()
+                .filter(|cached| Arc::ptr_eq(&cached.stats, &previous))
+            else {
+                continue;
+            };
+            match refreshed {
+                Ok(stats) => cached.stats = Arc::new(stats),
+                Err(e) => {
+                    tracing::warn!(
+                        "Refresh failed, collecting everything on the next request: {:?}",
+                        e
+                    );
+                    *entry = None;
+                }
+            }
+        }
+    }
+
+    fn configuration_for(&self, login: Option<&str>) -> Configuration {
+        match login {
+            Some(login) => self.configuration.for_member(login),
+            None => self.configuration.clone(),
+        }
+    }
+
+    fn is_member(&self, login: &str) -> bool {
+        self.configuration
+            .team_members()
+            .iter()
+            .any(|member| member.eq_ignore_ascii_case(login))
+    }
+}
+
+async fn root_file(
+    State(server): State<StatsServer>,
+    Path(file): Path<String>,
+    Query(query): Query<CardQuery>,
+    headers: HeaderMap,
+) -> Response {
+    server
+        .respond(None, &file, &query, &headers)
+        .await
+        .unwrap_or_else(error_response)
+}
+
+async fn member_file(
+    State(server): State<StatsServer>,
+    Path((login, file)): Path<(String, String)>,
+    Query(query): Query<CardQuery>,
+    headers: HeaderMap,
+) -> Response {
+    if !server.is_member(&login) {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+    server
+        .respond(Some(&login), &file, &query, &headers)
+        .await
+        .unwrap_or_else(error_response)
+}
+
+async fn webhook(State(server): State<StatsServer>, headers: HeaderMap, body: Bytes) -> StatusCode {
+    let (Some(webhook), Some(events)) = (&server.webhook, &server.events) else {
+        return StatusCode::NOT_FOUND;
+    };
+
+    let header = |name: &str| headers.get(name).and_then(|value| value.to_str().ok());
+    if !webhook.verify(header("x-hub-signature-256"), &body) {
+        tracing::warn!("Rejected a webhook delivery with a missing or invalid signature");
+        return StatusCode::UNAUTHORIZED;
+    }
+    // Gitea sends the same signature, under its own event header
+    let Some(event) = header("x-github-event").or_else(|| header("x-gitea-event")) else {
+        return StatusCode::BAD_REQUEST;
+    };
+    let Ok(payload) = serde_json::from_slice(&body) else {
+        return StatusCode::BAD_REQUEST;
+    };
+
+    let changes = Webhook::changes(event, &payload);
+    tracing::info!("Received a {} event, stale: {:?}", event, changes);
+    if changes.is_empty() {
+        return StatusCode::NO_CONTENT;
+    }
+    match events.send(changes) {
+        Ok(()) => StatusCode::ACCEPTED,
+        Err(_) => StatusCode::SERVICE_UNAVAILABLE,
+    }
+}
+
+fn error_response(error: StatsError) -> Response {
+    tracing::error!("Failed to serve statistics: {:?}", error);
+    match error {
+        StatsError::RateLimited { reset_at } => {
+            let retry_after = reset_at
+                .map(|reset_at| (reset_at - chrono::Utc::now()).num_seconds().max(1))
+                .unwrap_or(60);
+            (
+                StatusCode::SERVICE_UNAVAILABLE,
+                [(header::RETRY_AFTER, retry_after.to_string())],
+            )
+                .into_response()
+        }
+        StatsError::NotFound(_) => StatusCode::NOT_FOUND.into_response(),
+        StatsError::Auth { .. } | StatsError::Http(_) => StatusCode::BAD_GATEWAY.into_response(),
+        _ => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
+/// A strong validator derived from the response body
+fn etag(body: &str) -> String {
+    let mut hasher = DefaultHasher::new();
+    body.hash(&mut hasher);
+    format!("\"{:016x}\"", hasher.finish())
+}
+
+/// Whether an `If-None-Match` list names `etag`, or is `*`. The comparison is weak
+/// as RFC 9110 asks for, so `W/"tag"` matches `"tag"` as well.
+fn etag_matches(if_none_match: &str, etag: &str) -> bool {
+    if_none_match.trim() == "*"
+        || if_none_match
+            .split(',')
+            .map(|tag| tag.trim())
+            .map(|tag| tag.strip_prefix("W/").unwrap_or(tag))
+            .any(|tag| tag == etag)
+}
+
+fn header_value(value: &str) -> HeaderValue {
+    HeaderValue::from_str(value).expect("header values are ASCII")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use reqwest::StatusCode;
+    use serde_json::json;
+
+    use super::*;
+    use crate::service::test_support::{stand_in, stats, stats_builder, webhook_signature};
+
+    /// Serves the router with a loader that counts its collections instead of crawling
+    async fn server(cache_ttl_seconds: u64) -> (String, Arc<AtomicUsize>) {
+        let loads = Arc::new(AtomicUsize::new(0));
+        let counter = loads.clone();
+        let loader: StatsLoader = Arc::new(move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            Box::pin(async { Ok(stats()) })
+        });
+        let refresher: StatsRefresher =
+            Arc::new(|_, stats, _| Box::pin(async move { Ok(stats.as_ref().clone()) }));
+        let configuration = Configuration::from_json(json!({
+            "github_actor": "jane",
+            "cache_ttl_seconds": cache_ttl_seconds,
+        }));
+        let image_gen = ImageGen::new("resources/templates".to_string(), String::new());
+        let router = StatsServer::new(configuration, image_gen, loader, refresher).router();
+
+        let url = stand_in(router).await;
+
+        (url, loads)
+    }
+
+    #[tokio::test]
+    async fn serves_cards_and_statistics_with_their_content_type() {
+        let (url, _) = server(60).await;
+
+        for (path, content_type) in [
+            ("overview.svg", "image/svg+xml"),
+            ("overview_dark.svg", "image/svg+xml"),
+            ("stats.json", "application/json"),
+            ("metrics", OPENMETRICS_CONTENT_TYPE),
+        ] {
+            let response = reqwest::get(format!("{url}/{path}")).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK, "{path}");
+            assert_eq!(response.headers()["content-type"], content_type, "{path}");
+        }
+        let missing = reqwest::get(format!("{url}/secrets.txt")).await.unwrap();
+        assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn dark_variant_matches_the_dark_theme() {
+        let (url, _) = server(60).await;
+        let body =
+            |path: String| async move { reqwest::get(path).await.unwrap().text().await.unwrap() };
+
+        assert_eq!(
+            body(format!("{url}/overview_dark.svg")).await,
+            body(format!("{url}/overview.svg?theme=dark")).await
+        );
+        assert_ne!(
+            body(format!("{url}/overview_dark.svg")).await,
+            body(format!("{url}/overview.svg")).await
+        );
+    }
+
+    #[tokio::test]
+    async fn cache_control_counts_down_the_ttl() {
+        let (url, _) = server(60).await;
+
+        let response = reqwest::get(format!("{url}/stats.json")).await.unwrap();
+
+        let cache_control = response.headers()["cache-control"].to_str().unwrap();
+        let max_age = cache_control
+            .strip_prefix("public, max-age=")
+            .and_then(|max_age| max_age.parse::<u64>().ok())
+            .unwrap();
+        assert!((58..=60).contains(&max_age), "{cache_control}");
+    }
+
+    #[tokio::test]
+    async fn matching_etags_are_not_modified() {
+        let (url, _) = server(60).await;
+        let client = reqwest::Client::new();
+        let first = client
+            .get(format!("{url}/overview.svg"))
+            .send()
+            .await
+            .unwrap();
+        let etag = first.headers()["etag"].to_str().unwrap().to_string();
+
+        for if_none_match in [
+            etag.clone(),
+            format!("W/{etag}"),
+            format!("\"other\", {etag}"),
+            "*".to_string(),
+        ] {
+            let response = client
+                .get(format!("{url}/overview.svg"))
+                .header("if-none-match", &if_none_match)
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(
+                response.status(),
+                StatusCode::NOT_MODIFIED,
+                "{if_none_match}"
+            );
+            assert_eq!(response.headers()["etag"], etag.as_str());
+        }
+
+        let stale = client
+            .get(format!("{url}/overview.svg"))
+            .header("if-none-match", "\"other\", W/\"another\"")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(stale.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn statistics_are_collected_again_once_the_ttl_ran_out() {
+        let (cached, cached_loads) = server(60).await;
+        let (expired, expired_loads) = server(0).await;
+
+        for url in [&cached, &cached, &expired, &expired] {
+            let response = reqwest::get(format!("{url}/stats.json")).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+
+        assert_eq!(cached_loads.load(Ordering::SeqCst), 1);
+        assert_eq!(expired_loads.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn members_are_served_their_own_statistics_only() {
+        // each source merged into the statistics adds its own stargazers
+        let loader: StatsLoader = Arc::new(|configuration| {
+            Box::pin(async move {
+                Ok(stats_builder()
+                    .name(configuration.account().to_string())
+                    .stargazers(3 + 100 * configuration.sources().len() as i64)
+                    .build()
+                    .unwrap())
+            })
+        });
+        let refresher: StatsRefresher =
+            Arc::new(|_, stats, _| Box::pin(async move { Ok(stats.as_ref().clone()) }));
+        let configuration = Configuration::from_json(json!({
+            "github_actor": "acme-team",
+            "organization": "acme",
+            "team_members": "jane",
+            "sources": [{ "provider": "gitlab", "token_env": "GITLAB_TOKEN" }],
+        }));
+        let image_gen = ImageGen::new("resources/templates".to_string(), String::new());
+        let url =
+            stand_in(StatsServer::new(configuration, image_gen, loader, refresher).router()).await;
+        let summary = |path: String| async move {
+            reqwest::get(path)
+                .await
+                .unwrap()
+                .json::<serde_json::Value>()
+                .await
+                .unwrap()
+        };
+
+        let team = summary(format!("{url}/stats.json")).await;
+        let member = summary(format!("{url}/jane/stats.json")).await;
+
+        assert_eq!(team["name"], "acme");
+        assert_eq!(team["stargazers"], 103);
+        assert_eq!(member["name"], "jane");
+        assert_eq!(member["stargazers"], 3);
+    }
+
+    #[tokio::test]
+    async fn a_burst_of_webhook_deliveries_is_merged_into_one_refresh() {
+        let refreshes = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = refreshes.clone();
+        let loader: StatsLoader = Arc::new(|_| Box::pin(async { Ok(stats()) }));
+        let refresher: StatsRefresher = Arc::new(move |_, stats, changes| {
+            recorded.lock().unwrap().push(changes);
+            Box::pin(async move { Ok(stats.as_ref().clone()) })
+        });
+        let configuration = Configuration::from_json(json!({
+            "github_actor": "jane",
+            "webhook_secret": "secret",
+            "webhook_debounce_seconds": 1,
+        }));
+        let image_gen = ImageGen::new("resources/templates".to_string(), String::new());
+        let server = StatsServer::new(configuration, image_gen, loader, refresher);
+        let url = stand_in(server.receiving_events().router()).await;
+        let client = reqwest::Client::new();
+        // only cached statistics are refreshed
+        client
+            .get(format!("{url}/stats.json"))
+            .send()
+            .await
+            .unwrap();
+
+        for (event, payload) in [
+            ("push", json!({ "repository": { "full_name": "jane/a" } })),
+            ("star", json!({ "repository": { "full_name": "jane/a" } })),
+            ("push", json!({ "repository": { "full_name": "jane/b" } })),
+        ] {
+            let body = payload.to_string();
+            let response = client
+                .post(format!("{url}/webhook"))
+                .header("x-github-event", event)
+                .header(
+                    "x-hub-signature-256",
+                    webhook_signature("secret", body.as_bytes()),
+                )
+                .body(body)
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::ACCEPTED);
+        }
+        // the quiet period after the last delivery, and some slack for the refresh
+        tokio::time::sleep(Duration::from_millis(2500)).await;
+
+        assert_eq!(
+            *refreshes.lock().unwrap(),
+            [vec![
+                StatsChange::Repositories,
+                StatsChange::LinesChanged("jane/a".to_string()),
+                StatsChange::LinesChanged("jane/b".to_string()),
+                StatsChange::Contributions,
+            ]]
+        );
+    }
+
+    #[tokio::test]
+    async fn unsigned_webhook_deliveries_are_rejected() {
+        let loader: StatsLoader = Arc::new(|_| Box::pin(async { Ok(stats()) }));
+        let refresher: StatsRefresher =
+            Arc::new(|_, stats, _| Box::pin(async move { Ok(stats.as_ref().clone()) }));
+        let configuration = Configuration::from_json(json!({
+            "github_actor": "jane",
+            "webhook_secret": "secret",
+        }));
+        let image_gen = ImageGen::new("resources/templates".to_string(), String::new());
+        let server = StatsServer::new(configuration, image_gen, loader, refresher);
+        let url = stand_in(server.receiving_events().router()).await;
+
+        let response = reqwest::Client::new()
+            .post(format!("{url}/webhook"))
+            .header("x-github-event", "star")
+            .header("x-hub-signature-256", webhook_signature("guess", b"{}"))
+            .body("{}")
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+}
@@ -0,0 +1,52 @@
+use axum::Router;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::domain::{Language, Metric, Stats, StatsBuilder};
+
+/// Serves `app` on a free local port in place of a forge or an API, returns its URL
+pub async fn stand_in(app: Router) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await });
+    url
+}
+
+/// `jane`'s statistics with every required field set, for tests to override
+pub fn stats_builder() -> StatsBuilder {
+    let mut builder = StatsBuilder::default();
+    builder
+        .name("jane".to_string())
+        .stargazers(3)
+        .forks(1)
+        .languages(vec![])
+        .repos(vec![])
+        .total_contributions(Metric::Available(12))
+        .lines_changed_by_repo(Metric::Available(vec![]))
+        .views(Metric::Available(vec![]))
+        .contribution_calendar(Metric::Available(vec![]));
+    builder
+}
+
+/// A language of `size` bytes out of the 400 the fixture's languages add up to
+pub fn language(name: &str, size: i64) -> (String, Language) {
+    let mut language = Language::new(name.to_string(), size, 1, "#000".to_string(), 0.0);
+    language.set_proportion(400);
+    (name.to_string(), language)
+}
+
+/// `jane`'s statistics with two languages, the cards showing only the first
+pub fn stats() -> Stats {
+    stats_builder()
+        .languages(vec![language("Rust", 300), language("Shell", 100)])
+        .languages_limit(1)
+        .build()
+        .unwrap()
+}
+
+/// The `X-Hub-Signature-256` header GitHub sends with a delivery of `body`
+pub fn webhook_signature(secret: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
@@ -0,0 +1,130 @@
+/// Advance widths of the printable ASCII characters in Verdana, from `' '` to `'~'`,
+/// in font units of 2048 per em
+#[rustfmt::skip]
+const VERDANA_WIDTHS: [u16; 95] = [
+    720, 824, 1004, 1877, 1303, 2224, 1510, 553, 913, 913, 1303, 1877, 745, 864, 745, 1300, // ' ' to '/'
+    1302, 1302, 1302, 1302, 1302, 1302, 1302, 1302, 1302, 1302, // '0' to '9'
+    887, 887, 1877, 1877, 1877, 1116, 2048, // ':' to '@'
+    1401, 1405, 1435, 1577, 1294, 1178, 1589, 1540, 861, 921, 1419, 1141, 1747, // 'A' to 'M'
+    1532, 1613, 1239, 1613, 1424, 1400, 1245, 1499, 1401, 2025, 1403, 1246, 1403, // 'N' to 'Z'
+    913, 1300, 913, 1877, 1303, 1303, // '[' to '`'
+    1255, 1283, 1114, 1283, 1219, 720, 1283, 1298, 562, 705, 1212, 562, 1992, // 'a' to 'm'
+    1298, 1239, 1283, 1283, 874, 1064, 807, 1298, 1212, 1675, 1212, 1212, 1076, // 'n' to 'z'
+    1303, 913, 1303, 1877, // '{' to '~'
+];
+
+/// Advance widths of the printable ASCII characters in Helvetica, from `' '` to `'~'`,
+/// in font units of 1000 per em. Arial shares them.
+#[rustfmt::skip]
+const HELVETICA_WIDTHS: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278, // ' ' to '/'
+    556, 556, 556, 556, 556, 556, 556, 556, 556, 556, // '0' to '9'
+    278, 278, 584, 584, 584, 556, 1015, // ':' to '@'
+    667, 667, 722, 722, 667, 611, 778, 722, 278, 500, 667, 556, 833, // 'A' to 'M'
+    722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667, 667, 611, // 'N' to 'Z'
+    278, 278, 278, 469, 556, 333, // '[' to '`'
+    556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500, 222, 833, // 'a' to 'm'
+    556, 556, 556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, // 'n' to 'z'
+    334, 260, 334, 584, // '{' to '~'
+];
+
+/// Advance widths of the printable ASCII characters in bold Helvetica, from `' '` to `'~'`,
+/// in font units of 1000 per em
+#[rustfmt::skip]
+const HELVETICA_BOLD_WIDTHS: [u16; 95] = [
+    278, 333, 474, 556, 556, 889, 722, 238, 333, 333, 389, 584, 278, 333, 278, 278, // ' ' to '/'
+    556, 556, 556, 556, 556, 556, 556, 556, 556, 556, // '0' to '9'
+    333, 333, 584, 584, 584, 611, 975, // ':' to '@'
+    722, 722, 722, 722, 667, 611, 778, 722, 278, 556, 722, 611, 833, // 'A' to 'M'
+    722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667, 667, 611, // 'N' to 'Z'
+    333, 278, 333, 584, 556, 333, // '[' to '`'
+    556, 611, 556, 611, 556, 333, 611, 611, 278, 278, 556, 278, 889, // 'a' to 'm'
+    611, 611, 611, 611, 389, 556, 333, 611, 556, 778, 556, 556, 500, // 'n' to 'z'
+    389, 280, 389, 584, // '{' to '~'
+];
+
+/// A typeface text is measured in, without a renderer at hand
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Font {
+    /// The font of shields-style badges
+    Verdana,
+    /// The font of the cards, or the Arial most systems substitute for it
+    Helvetica,
+    HelveticaBold,
+}
+
+impl Font {
+    /// Width in pixels of `text` set at `font_size` pixels. Characters outside of ASCII
+    /// are assumed as wide as `m`.
+    pub fn text_width(self, text: &str, font_size: f64) -> f64 {
+        let (widths, units_per_em) = match self {
+            Font::Verdana => (&VERDANA_WIDTHS, 2048.0),
+            Font::Helvetica => (&HELVETICA_WIDTHS, 1000.0),
+            Font::HelveticaBold => (&HELVETICA_BOLD_WIDTHS, 1000.0),
+        };
+        let fallback = widths[usize::from(b'm' - b' ')];
+        let units = text
+            .chars()
+            .map(|c| {
+                u32::from(c)
+                    .checked_sub(u32::from(' '))
+                    .and_then(|idx| widths.get(idx as usize))
+                    .copied()
+                    .unwrap_or(fallback) as f64
+            })
+            .sum::<f64>();
+
+        units * font_size / units_per_em
+    }
+
+    /// Shortens `text` with an ellipsis until it fits into `max_width` pixels
+    pub fn truncate(self, text: &str, font_size: f64, max_width: f64) -> String {
+        if self.text_width(text, font_size) <= max_width {
+            return text.to_string();
+        }
+
+        let mut truncated = text.to_string();
+        while truncated.pop().is_some() {
+            let candidate = format!("{}…", truncated.trim_end());
+            if self.text_width(&candidate, font_size) <= max_width {
+                return candidate;
+            }
+        }
+        "…".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verdana_widths_match_the_badges() {
+        // b, u, i, l and d add up to 4988 of 2048 units per em
+        assert!((Font::Verdana.text_width("build", 11.0) - 26.791).abs() < 0.001);
+        assert!((Font::Verdana.text_width("1.2k", 11.0) - 24.498).abs() < 0.001);
+        assert_eq!(Font::Verdana.text_width("", 11.0), 0.0);
+        // characters outside of ASCII are as wide as `m`
+        assert_eq!(
+            Font::Verdana.text_width("é", 11.0),
+            Font::Verdana.text_width("m", 11.0)
+        );
+    }
+
+    #[test]
+    fn text_is_truncated_with_an_ellipsis_at_the_limit() {
+        let font = Font::Helvetica;
+        let limit = font.text_width("Hello…", 10.0);
+
+        assert_eq!(font.truncate("Hello", 10.0, limit), "Hello");
+        assert_eq!(
+            font.truncate("Hello…", 10.0, limit),
+            "Hello…",
+            "text exactly at the limit fits"
+        );
+        // the space before the cut is dropped
+        assert_eq!(font.truncate("Hello World", 10.0, limit), "Hello…");
+        assert_eq!(font.truncate("Hello World", 10.0, limit - 0.01), "Hell…");
+        assert_eq!(font.truncate("Hello World", 10.0, 1.0), "…");
+    }
+}
@@ -0,0 +1,151 @@
+use hmac::{Hmac, Mac};
+use secrecy::{ExposeSecret, SecretString};
+use serde_json::Value;
+use sha2::Sha256;
+
+use crate::domain::StatsChange;
+
+const SIGNATURE_PREFIX: &str = "sha256=";
+
+/// Verifies webhook deliveries and tells which parts of the statistics they make stale
+#[derive(Debug, Clone)]
+pub struct Webhook {
+    secret: SecretString,
+}
+
+impl Webhook {
+    pub fn new(secret: SecretString) -> Self {
+        Self { secret }
+    }
+
+    /// Checks the `X-Hub-Signature-256` header, the HMAC-SHA256 of the raw body
+    /// keyed with the webhook secret
+    pub fn verify(&self, signature: Option<&str>, body: &[u8]) -> bool {
+        let Some(signature) = signature
+            .and_then(|signature| signature.strip_prefix(SIGNATURE_PREFIX))
+            .and_then(|signature| hex::decode(signature).ok())
+        else {
+            return false;
+        };
+
+        let Ok(mut mac) = Hmac::<Sha256>::new_from_slice(self.secret.expose_secret().as_bytes())
+        else {
+            return false;
+        };
+        mac.update(body);
+        // constant time, the comparison doesn't leak how much of the signature matched
+        mac.verify_slice(&signature).is_ok()
+    }
+
+    /// The parts of the statistics an event of type `event` makes stale,
+    /// empty for events that don't affect them
+    pub fn changes(event: &str, payload: &Value) -> Vec<StatsChange> {
+        let repository = payload
+            .pointer("/repository/full_name")
+            .and_then(Value::as_str);
+
+        match event {
+            "star" | "watch" | "fork" | "repository" | "public" => {
+                vec![StatsChange::Repositories]
+            }
+            "push" => repository
+                .map(|repository| StatsChange::LinesChanged(repository.to_string()))
+                .into_iter()
+                .chain([StatsChange::Contributions])
+                .collect(),
+            "pull_request" | "pull_request_review" | "issues" => {
+                vec![StatsChange::Contributions]
+            }
+            // a release touches no single metric, but usually follows a burst of other activity
+            "release" => vec![StatsChange::Everything],
+            _ => vec![],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::service::test_support::webhook_signature;
+
+    const BODY: &[u8] = br#"{"action":"created"}"#;
+
+    fn webhook() -> Webhook {
+        Webhook::new(SecretString::from("It's a Secret to Everybody"))
+    }
+
+    #[test]
+    fn signature_of_the_body_with_the_secret_is_accepted() {
+        let signature = webhook_signature("It's a Secret to Everybody", BODY);
+
+        assert!(webhook().verify(Some(&signature), BODY));
+    }
+
+    #[test]
+    fn signature_with_another_secret_or_body_is_rejected() {
+        let other_secret = webhook_signature("another secret", BODY);
+        let other_body = webhook_signature("It's a Secret to Everybody", b"{}");
+
+        assert!(!webhook().verify(Some(&other_secret), BODY));
+        assert!(!webhook().verify(Some(&other_body), BODY));
+    }
+
+    #[test]
+    fn signature_without_the_prefix_is_rejected() {
+        let signature = webhook_signature("It's a Secret to Everybody", BODY);
+        let unprefixed = signature.strip_prefix(SIGNATURE_PREFIX).unwrap();
+
+        assert!(!webhook().verify(Some(unprefixed), BODY));
+        assert!(!webhook().verify(Some(&format!("sha1={unprefixed}")), BODY));
+    }
+
+    #[test]
+    fn missing_signature_is_rejected() {
+        assert!(!webhook().verify(None, BODY));
+        assert!(!webhook().verify(Some(""), BODY));
+    }
+
+    #[test]
+    fn signature_that_is_not_hex_is_rejected() {
+        assert!(!webhook().verify(Some("sha256=not-hex"), BODY));
+        assert!(!webhook().verify(Some("sha256=abc"), BODY));
+    }
+
+    #[test]
+    fn events_map_to_the_parts_they_make_stale() {
+        let payload = json!({ "repository": { "full_name": "jane/stats" } });
+        let cases = [
+            ("star", vec![StatsChange::Repositories]),
+            ("watch", vec![StatsChange::Repositories]),
+            ("fork", vec![StatsChange::Repositories]),
+            ("repository", vec![StatsChange::Repositories]),
+            ("public", vec![StatsChange::Repositories]),
+            (
+                "push",
+                vec![
+                    StatsChange::LinesChanged("jane/stats".to_string()),
+                    StatsChange::Contributions,
+                ],
+            ),
+            ("pull_request", vec![StatsChange::Contributions]),
+            ("pull_request_review", vec![StatsChange::Contributions]),
+            ("issues", vec![StatsChange::Contributions]),
+            ("release", vec![StatsChange::Everything]),
+            ("ping", vec![]),
+        ];
+
+        for (event, changes) in cases {
+            assert_eq!(Webhook::changes(event, &payload), changes, "{event}");
+        }
+    }
+
+    #[test]
+    fn push_without_a_repository_only_touches_contributions() {
+        assert_eq!(
+            Webhook::changes("push", &json!({})),
+            [StatsChange::Contributions]
+        );
+    }
+}